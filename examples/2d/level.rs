@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_svg::prelude::*;
+
+#[path = "../common/lib.rs"]
+mod common;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "2d_level".to_string(),
+                resolution: (600., 600.).into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .add_plugins((common::CommonPlugin, bevy_svg::prelude::SvgPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, print_markers)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut prefabs: ResMut<SvgMarkerPrefabs>,
+) {
+    // The guides only help while drawing the level, so they are not loaded
+    let svg = asset_server.load_with_settings("level.svg", |settings: &mut SvgLoaderSettings| {
+        settings.layers = LayerFilter::Exclude(vec!["Guides".to_string()]);
+    });
+
+    // Every spawn point of the level gets a star, which is placed at the marker
+    let star: Handle<Svg> = asset_server.load("spawn_point.svg");
+    prefabs.add(
+        MarkerSelector::IdPrefix("spawn_".to_string()),
+        move |entity, _marker| {
+            entity.insert((star.clone(), Origin::Center, common::DontChange));
+        },
+    );
+
+    commands.spawn(Camera2dBundle::default());
+    commands.spawn(Svg2dBundle {
+        svg,
+        origin: Origin::Center,
+        ..Default::default()
+    });
+}
+
+/// Prints the markers of the level, once it is shown.
+fn print_markers(mut svg_events: EventReader<SvgEvent>, svgs: Res<Assets<Svg>>) {
+    for event in svg_events.read() {
+        let SvgEvent::Ready { handle, .. } = event else {
+            continue;
+        };
+        let Some(svg) = svgs.get(handle) else {
+            continue;
+        };
+        for marker in svg.markers(&MarkerSelector::Data("team".to_string())) {
+            info!(
+                "`{}` of team `{}` at {}",
+                marker.id,
+                marker.data["team"],
+                marker.shape.position()
+            );
+        }
+        if let Some(MarkerShape::Path { points, .. }) = svg
+            .markers
+            .iter()
+            .find(|marker| marker.id == "patrol")
+            .map(|marker| &marker.shape)
+        {
+            info!("Patrol route: {points:?}");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy_svg::prelude::*;
+
+#[path = "../common/lib.rs"]
+mod common;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "2d_map".to_string(),
+                resolution: (600., 600.).into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .add_plugins((common::CommonPlugin, bevy_svg::prelude::SvgPlugin))
+        .add_systems(Startup, (setup, setup_legend))
+        .add_systems(Update, (zoom, cycle_stroke_width, highlight_roads))
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // The strokes are widened in the vertex shader and every path gets its own index, so
+    // the stroke widths and the styles of the paths change without tessellating the map again
+    let svg = asset_server.load_with_settings("map.svg", |settings: &mut SvgLoaderSettings| {
+        settings.tessellation.stroke_normals = true;
+        settings.tessellation.index = SvgIndexAttribute::Path;
+    });
+    commands.spawn(Camera2dBundle::default());
+    commands.spawn((
+        Svg2dBundle {
+            svg,
+            origin: Origin::Center,
+            transform: Transform::from_scale(Vec3::splat(2.5)),
+            ..Default::default()
+        },
+        SvgStrokeWidth::default(),
+    ));
+}
+
+fn setup_legend(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands.spawn(
+        TextBundle::from_section(
+            "Up/Down - Zoom\nS - Cycle stroke widths\nH - Highlight roads",
+            TextStyle {
+                font,
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            right: Val::Px(15.0),
+            ..default()
+        }),
+    );
+}
+
+/// Zooms the camera, the contour lines with `vector-effect="non-scaling-stroke"` keep their
+/// width on screen.
+fn zoom(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut query: Query<&mut OrthographicProjection>,
+) {
+    let factor = if keyboard_input.pressed(KeyCode::Up) {
+        (-time.delta_seconds()).exp()
+    } else if keyboard_input.pressed(KeyCode::Down) {
+        time.delta_seconds().exp()
+    } else {
+        return;
+    };
+    for mut projection in &mut query {
+        projection.scale = (projection.scale * factor).clamp(0.05, 5.0);
+    }
+}
+
+/// Switches between scaling strokes, strokes which keep their width on screen and hairlines.
+fn cycle_stroke_width(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut query: Query<&mut SvgStrokeWidth>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::S) {
+        return;
+    }
+    for mut stroke_width in &mut query {
+        *stroke_width = match *stroke_width {
+            SvgStrokeWidth::Scaling => SvgStrokeWidth::NonScaling,
+            SvgStrokeWidth::NonScaling => SvgStrokeWidth::Pixels(1.0),
+            SvgStrokeWidth::Pixels(_) => SvgStrokeWidth::Scaling,
+        };
+        info!("Stroke width: {:?}", *stroke_width);
+    }
+}
+
+/// Colors the roads red and hides the river, or shows the map as it is drawn again.
+fn highlight_roads(
+    keyboard_input: Res<Input<KeyCode>>,
+    query: Query<&Handle<Svg>, With<SvgStrokeWidth>>,
+    mut svgs: ResMut<Assets<Svg>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::H) {
+        return;
+    }
+    for handle in &query {
+        let Some(svg) = svgs.get_mut(handle) else {
+            continue;
+        };
+        if !svg.path_styles.is_empty() {
+            svg.path_styles.clear();
+            continue;
+        }
+
+        let mut styles = Vec::new();
+        for (index, path) in svg.paths.iter().enumerate() {
+            let style = if path.id.starts_with("road_") {
+                SvgPathStyle {
+                    color: Some(Color::RED),
+                    stroke_width: 1.5,
+                    ..Default::default()
+                }
+            } else if path.id == "river" {
+                SvgPathStyle {
+                    visible: false,
+                    ..Default::default()
+                }
+            } else {
+                continue;
+            };
+            styles.push((index as u32, style));
+        }
+        svg.path_styles.extend(styles);
+    }
+}
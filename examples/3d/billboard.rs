@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_svg::prelude::*;
+
+#[path = "../common/lib.rs"]
+mod common;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "3d_billboard".to_string(),
+                resolution: (600., 600.).into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .add_plugins((common::CommonPlugin, bevy_svg::prelude::SvgPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, orbit_camera)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let svg = asset_server.load("spawn_point.svg");
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Plane::from_size(8.0).into()),
+        material: materials.add(Color::DARK_GREEN.into()),
+        ..Default::default()
+    });
+
+    // A row of stars for every mode, the stars in the back keep their size on screen
+    let modes = [
+        SvgBillboardMode::Full,
+        SvgBillboardMode::Cylindrical,
+        SvgBillboardMode::ScreenAligned,
+    ];
+    for (row, mode) in modes.into_iter().enumerate() {
+        for column in 0..3 {
+            let translation = Vec3::new(column as f32 * 2.0 - 2.0, 0.6, row as f32 * 2.0 - 2.0);
+            let constant_size = row == 0;
+            let scale = if constant_size { 2.0 } else { 0.05 };
+            commands.spawn((
+                Svg3dBundle {
+                    svg: svg.clone(),
+                    origin: Origin::Center,
+                    transform: Transform::from_translation(translation)
+                        .with_scale(Vec3::splat(scale)),
+                    ..Default::default()
+                },
+                SvgBillboard {
+                    mode,
+                    constant_size,
+                },
+            ));
+        }
+    }
+}
+
+/// Moves the camera around the stars, which keep facing it.
+fn orbit_camera(time: Res<Time>, mut query: Query<&mut Transform, With<Camera3d>>) {
+    for mut transform in &mut query {
+        let angle = time.elapsed_seconds() * 0.3;
+        let height = 3.0 + 2.5 * (time.elapsed_seconds() * 0.2).sin();
+        *transform = Transform::from_xyz(8.0 * angle.sin(), height, 8.0 * angle.cos())
+            .looking_at(Vec3::ZERO, Vec3::Y);
+    }
+}
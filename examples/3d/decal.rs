@@ -0,0 +1,103 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+use bevy_svg::prelude::*;
+
+#[path = "../common/lib.rs"]
+mod common;
+
+fn main() {
+    App::new()
+        .insert_resource(Msaa::Sample4)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "3d_decal".to_string(),
+                resolution: (600., 600.).into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .add_plugins((common::CommonPlugin, bevy_svg::prelude::SvgPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (move_decal, rotate_globe))
+        .run();
+}
+
+#[derive(Component)]
+struct Globe;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(-3.0, 4.0, 7.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            shadows_enabled: true,
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Plane::from_size(10.0).into()),
+        material: materials.add(Color::GRAY.into()),
+        ..Default::default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Cube::new(1.5).into()),
+        material: materials.add(Color::ALICE_BLUE.into()),
+        transform: Transform::from_xyz(-1.5, 0.75, 0.0),
+        ..Default::default()
+    });
+
+    // The star is projected straight down onto the ground and over the edge of the cube
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_xyz(-1.0, 1.5, 0.5).with_rotation(Quat::from_rotation_x(-FRAC_PI_2)),
+        ),
+        SvgDecal {
+            svg: asset_server.load("spawn_point.svg"),
+            size: Vec3::new(2.0, 2.0, 3.0),
+            ..Default::default()
+        },
+    ));
+
+    // The map is rolled around a lit cylinder, which casts a shadow. The map is 200 units
+    // wide, so a radius of `200 / TAU` covers the whole cylinder. The top of the map is at the
+    // translation of the entity
+    let map = asset_server.load_with_settings("map.svg", |settings: &mut SvgLoaderSettings| {
+        settings.render_3d.wrap = Some(SvgWrap::Cylinder {
+            radius: 200.0 / TAU,
+        });
+        settings.render_3d.lighting = SvgLighting::Lit;
+        settings.render_3d.cast_shadows = true;
+    });
+    commands.spawn((
+        Svg3dBundle {
+            svg: map,
+            transform: Transform::from_xyz(2.0, 3.2, 0.0).with_scale(Vec3::splat(0.015)),
+            ..Default::default()
+        },
+        Globe,
+        common::DontChange,
+    ));
+}
+
+/// Slides the decal back and forth, it is projected again onto the meshes underneath.
+fn move_decal(time: Res<Time>, mut query: Query<&mut Transform, With<SvgDecal>>) {
+    for mut transform in &mut query {
+        transform.translation.x = -1.0 + time.elapsed_seconds().sin();
+    }
+}
+
+fn rotate_globe(time: Res<Time>, mut query: Query<&mut Transform, With<Globe>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(time.delta_seconds() * 0.5);
+    }
+}
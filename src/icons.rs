@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{Asset, Handle},
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+use crate::svg::Svg;
+
+/// All icons of an SVG icon sheet, i.e. a file with top-level `<symbol>` elements which are
+/// usually shown with `<use>`.
+///
+/// The loader adds this asset as `"IconSheet"`, e.g. `"feather.svg#IconSheet"`, to every file
+/// with at least one symbol that has an `id` and a `viewBox`. Each symbol is also added as a
+/// labeled [`Svg`] and mesh, `"Icon/<id>"` and `"Icon/<id>/mesh"`, sized by the `viewBox` of
+/// the symbol, so a single icon can be loaded directly with e.g. `"feather.svg#Icon/home"`.
+/// Their labels don't collide with the `"Svg/<id>"` labels of
+/// [`SvgLoaderSettings::element_assets`](crate::loader::SvgLoaderSettings::element_assets).
+#[derive(Asset, Clone, Debug, Default, Reflect)]
+#[reflect(Default, Debug)]
+pub struct SvgIconSheet {
+    /// The ids of all icons in document order.
+    pub ids: Vec<String>,
+    /// The [`Svg`] of every icon by its id.
+    pub icons: HashMap<String, Handle<Svg>>,
+}
+
+impl SvgIconSheet {
+    /// Returns the [`Svg`] of the icon with the given `id`.
+    pub fn get(&self, id: &str) -> Option<&Handle<Svg>> {
+        self.icons.get(id)
+    }
+
+    /// Returns the ids and [`Svg`]s of all icons in document order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Handle<Svg>)> {
+        self.ids
+            .iter()
+            .filter_map(|id| Some((id.as_str(), self.icons.get(id)?)))
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::HashSet;
+
+use bevy::{
+    asset::{AssetEvent, AssetId, Assets, Handle},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::{Changed, With, Without},
+        reflect::ReflectComponent,
+        system::{Commands, Query, ResMut, SystemParam},
+    },
+    hierarchy::{BuildChildren, Children, DespawnRecursiveExt, Parent},
+    math::Vec3,
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::{camera::Camera, mesh::Mesh, prelude::SpatialBundle},
+    transform::components::{GlobalTransform, Transform},
+};
+
+#[cfg(all(feature = "2d", feature = "3d"))]
+use crate::render::Svg3d;
+use crate::{origin::Origin, plugin, svg::Svg};
+
+/// Minimal distance along the z-axis between the paths of layers without a
+/// [`LayerDepth::z`](crate::svg::LayerDepth::z), which keeps them in paint order.
+const LAYER_Z_STEP: f32 = 0.001;
+
+#[derive(Clone, Component, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+/// Shows an [`Svg`] as one child entity per top-level layer, each with its own mesh, depth
+/// and parallax factor, instead of a single flat mesh.
+///
+/// Spawn it together with a [`SpatialBundle`] instead of a [`Handle<Svg>`] or an SVG bundle.
+/// When the [`Svg`] is loaded or modified, a child with a [`SvgParallaxLayer`] and the
+/// [`Svg`] of each layer from [`Svg::top_level_layers`] is spawned. The children are regular
+/// SVG entities, they get the [`Origin`] of this entity, if it has one, and a
+/// [`Svg3d`](crate::render::Svg3d) marker, if this entity has one.
+pub struct SvgLayers(pub Handle<Svg>);
+
+#[derive(Clone, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// A layer of [`SvgLayers`], which is placed at `z` and moved by the parallax relative to
+/// the active camera. Both can be changed at runtime.
+pub struct SvgParallaxLayer {
+    /// The name of the layer, empty for the paths outside of any layer.
+    pub name: String,
+    /// Translation of the layer along the z-axis, relative to its parent.
+    pub z: f32,
+    /// How much the layer moves with the world relative to the camera, see
+    /// [`LayerDepth::parallax`](crate::svg::LayerDepth::parallax).
+    pub parallax: f32,
+}
+
+/// The mesh of the [`Svg`] the layers of a [`SvgLayers`] entity were last spawned from.
+#[derive(Component)]
+pub(crate) struct SvgLayersMesh(AssetId<Mesh>);
+
+type SvgLayersComponents = (
+    Entity,
+    &'static SvgLayers,
+    Option<&'static SvgLayersMesh>,
+    Option<&'static Origin>,
+    Option<&'static Children>,
+);
+
+/// The entities with [`SvgLayers`] and their spawned layers.
+#[derive(SystemParam)]
+pub(crate) struct SvgLayersQuery<'w, 's> {
+    svg_layers: Query<'w, 's, SvgLayersComponents>,
+    changed: Query<'w, 's, (), Changed<SvgLayers>>,
+    layers: Query<'w, 's, (), With<SvgParallaxLayer>>,
+}
+
+/// Spawns the layers of all [`SvgLayers`] which are new or whose [`Svg`] was loaded, or
+/// modified with a new or changed mesh, replacing their previous layers.
+///
+/// Modifications which keep the geometry, like changed path styles, keep the layers. The
+/// previous layers are despawned, which drops the last handles of their assets.
+pub(crate) fn spawn_svg_layers(
+    mut commands: Commands,
+    mut svg_events: EventReader<AssetEvent<Svg>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut svgs: ResMut<Assets<Svg>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: SvgLayersQuery,
+    #[cfg(all(feature = "2d", feature = "3d"))] svg_3d_markers: Query<(), With<Svg3d>>,
+) {
+    let changed_meshes = plugin::changed_meshes(&mut mesh_events);
+    let mut loaded = HashSet::new();
+    let mut modified = HashSet::new();
+    for event in svg_events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } => loaded.insert(*id),
+            AssetEvent::Modified { id } => modified.insert(*id),
+            _ => false,
+        };
+    }
+
+    for (entity, SvgLayers(handle), spawned_mesh, origin, children) in &query.svg_layers {
+        let Some(svg) = svgs.get(handle) else {
+            continue;
+        };
+        let mesh_changed = spawned_mesh.map_or(true, |SvgLayersMesh(mesh)| {
+            *mesh != svg.mesh.id() || changed_meshes.contains(mesh)
+        });
+        let respawn = query.changed.contains(entity)
+            || loaded.contains(&handle.id())
+            || (modified.contains(&handle.id()) && mesh_changed);
+        if !respawn {
+            continue;
+        }
+        let layers = svg.top_level_layers();
+        commands.entity(entity).insert(SvgLayersMesh(svg.mesh.id()));
+
+        for child in children.into_iter().flatten() {
+            if query.layers.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        // Layers are stacked where their paths would be in the mesh of the whole SVG
+        let mut z = 0.0;
+        for (name, depth, mut layer) in layers {
+            let z_step = layer.render_3d.paint_order.z_step().max(LAYER_Z_STEP);
+            let layer_entity = SvgParallaxLayer {
+                z: depth.z.unwrap_or(z),
+                parallax: depth.parallax.unwrap_or(1.0),
+                name: name.clone(),
+            };
+            z = layer.paths.iter().fold(z, |z, _| z + z_step);
+            layer.mesh = meshes.add(layer.tessellate());
+            let mut child = commands.spawn((
+                Name::new(if name.is_empty() {
+                    layer.name.clone()
+                } else {
+                    name
+                }),
+                SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, layer_entity.z)),
+                svgs.add(layer),
+                layer_entity,
+            ));
+            if let Some(origin) = origin {
+                child.insert(*origin);
+            }
+            #[cfg(all(feature = "2d", feature = "3d"))]
+            if svg_3d_markers.contains(entity) {
+                child.insert(Svg3d);
+            }
+            child.set_parent(entity);
+        }
+    }
+}
+
+type Hierarchy = (&'static Transform, Option<&'static Parent>);
+
+/// Computes the global transform of `entity` from the transforms of its ancestors, because
+/// the [`GlobalTransform`]s are not yet propagated when the parallax is applied.
+fn global_transform(
+    entity: Entity,
+    hierarchy: &Query<Hierarchy, Without<SvgParallaxLayer>>,
+) -> GlobalTransform {
+    let mut global = GlobalTransform::IDENTITY;
+    let mut next = Some(entity);
+    while let Some((transform, parent)) = next.and_then(|entity| hierarchy.get(entity).ok()) {
+        global = GlobalTransform::from(*transform) * global;
+        next = parent.map(Parent::get);
+    }
+    global
+}
+
+/// Moves every [`SvgParallaxLayer`] by its parallax relative to the active camera with the
+/// highest order.
+pub(crate) fn apply_parallax(
+    cameras: Query<(Entity, &Camera)>,
+    hierarchy: Query<Hierarchy, Without<SvgParallaxLayer>>,
+    mut layers: Query<(&SvgParallaxLayer, &Parent, &mut Transform)>,
+) {
+    let Some(camera) = cameras
+        .iter()
+        .filter(|(_, camera)| camera.is_active)
+        .max_by_key(|(_, camera)| camera.order)
+        .map(|(entity, _)| global_transform(entity, &hierarchy).translation())
+    else {
+        return;
+    };
+
+    for (layer, parent, mut transform) in &mut layers {
+        let offset = Vec3::new(camera.x, camera.y, 0.0) * (1.0 - layer.parallax);
+        let offset = global_transform(parent.get(), &hierarchy)
+            .affine()
+            .inverse()
+            .transform_vector3(offset);
+        let translation = Vec3::new(offset.x, offset.y, layer.z);
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        ecs::schedule::{apply_deferred, IntoSystemConfigs},
+        hierarchy::BuildWorldChildren,
+    };
+
+    use super::*;
+    use crate::{loader::tests::load, SvgLoaderSettings};
+
+    const LAYERS: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"
+        xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" viewBox="0 0 10 10">
+        <g inkscape:groupmode="layer" inkscape:label="Back" data-parallax="0.5">
+            <rect width="10" height="10"/>
+        </g>
+        <g inkscape:groupmode="layer" inkscape:label="Front"><circle r="2"/></g>
+    </svg>"#;
+
+    /// Spawns an entity with the [`SvgLayers`] of a loaded SVG with two layers.
+    fn spawn_layers() -> (App, Entity) {
+        let mut app = crate::loader::tests::app(&[("layers.svg", LAYERS)]);
+        app.add_systems(Update, (spawn_svg_layers, apply_deferred).chain());
+        let handle = load(&mut app, "layers.svg", SvgLoaderSettings::default());
+        let entity = app
+            .world
+            .spawn((SvgLayers(handle), SpatialBundle::default()))
+            .id();
+        app.update();
+        (app, entity)
+    }
+
+    /// Returns the layer children of `entity`.
+    fn layers(app: &App, entity: Entity) -> Vec<(Entity, SvgParallaxLayer)> {
+        app.world
+            .get::<Children>(entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| Some((*child, app.world.get::<SvgParallaxLayer>(*child)?.clone())))
+            .collect()
+    }
+
+    /// Returns the number of [`Svg`] and [`Mesh`] assets.
+    fn asset_count(app: &App) -> (usize, usize) {
+        (
+            app.world.resource::<Assets<Svg>>().len(),
+            app.world.resource::<Assets<Mesh>>().len(),
+        )
+    }
+
+    #[test]
+    fn spawns_one_child_per_layer() {
+        let (app, entity) = spawn_layers();
+        let layers = layers(&app, entity);
+        assert_eq!(
+            layers.iter().map(|(_, layer)| layer).collect::<Vec<_>>(),
+            [
+                &SvgParallaxLayer {
+                    name: "Back".into(),
+                    z: 0.0,
+                    parallax: 0.5,
+                },
+                &SvgParallaxLayer {
+                    name: "Front".into(),
+                    z: LAYER_Z_STEP,
+                    parallax: 1.0,
+                }
+            ]
+        );
+        for (layer, _) in layers {
+            let handle = app.world.get::<Handle<Svg>>(layer).unwrap();
+            let svg = app.world.resource::<Assets<Svg>>().get(handle).unwrap();
+            assert_eq!(svg.paths.len(), 1);
+            assert!(app.world.resource::<Assets<Mesh>>().contains(&svg.mesh));
+        }
+    }
+
+    #[test]
+    fn respawns_layers_with_new_geometry() {
+        let (mut app, entity) = spawn_layers();
+        // Let the assets of the first layers settle
+        app.update();
+        let before = layers(&app, entity);
+        let assets = asset_count(&app);
+
+        let handle = app.world.get::<SvgLayers>(entity).unwrap().0.clone();
+        let mesh = app
+            .world
+            .resource_mut::<Assets<Svg>>()
+            .get_mut(&handle)
+            .unwrap()
+            .mesh
+            .clone();
+        app.world.resource_mut::<Assets<Mesh>>().get_mut(&mesh);
+        // The asset events are sent after the update of the app, so they are only handled
+        // in the next, and the dropped handles of the previous layers in the one after. The
+        // meshes of the dropped layers can be freed one update later, because the assets
+        // of the layers and the meshes are tracked in any order
+        app.update();
+        app.update();
+        app.update();
+        app.update();
+
+        let after = layers(&app, entity);
+        assert_eq!(after.len(), 2);
+        assert!(after
+            .iter()
+            .all(|(layer, _)| !before.iter().any(|(b, _)| b == layer)));
+        assert_eq!(asset_count(&app), assets);
+    }
+
+    #[test]
+    fn keeps_layers_with_same_geometry() {
+        let (mut app, entity) = spawn_layers();
+        let before = layers(&app, entity);
+
+        let handle = app.world.get::<SvgLayers>(entity).unwrap().0.clone();
+        app.world.resource_mut::<Assets<Svg>>().get_mut(&handle);
+        app.update();
+        app.update();
+
+        assert_eq!(layers(&app, entity), before);
+    }
+
+    #[test]
+    fn parallax_moves_layers_relative_to_camera() {
+        let mut app = App::new();
+        app.add_systems(Update, apply_parallax);
+        // The camera is moved through its parent in the same frame
+        let rig = app.world.spawn(Transform::from_xyz(4.0, 0.0, 0.0)).id();
+        app.world
+            .spawn((Camera::default(), Transform::from_xyz(6.0, 2.0, 0.0)))
+            .set_parent(rig);
+        let svg_layers = app
+            .world
+            .spawn(Transform::from_scale(Vec3::splat(2.0)))
+            .id();
+        let layer = |parallax| SvgParallaxLayer {
+            parallax,
+            z: 1.0,
+            ..Default::default()
+        };
+        let back = app
+            .world
+            .spawn((layer(0.5), Transform::default()))
+            .set_parent(svg_layers)
+            .id();
+        let front = app
+            .world
+            .spawn((layer(1.0), Transform::default()))
+            .set_parent(svg_layers)
+            .id();
+        app.update();
+
+        // Half of the camera movement, in the scaled space of the parent
+        assert_eq!(
+            app.world.get::<Transform>(back).unwrap().translation,
+            Vec3::new(2.5, 0.5, 1.0)
+        );
+        assert_eq!(
+            app.world.get::<Transform>(front).unwrap().translation,
+            Vec3::new(0.0, 0.0, 1.0)
+        );
+    }
+}
@@ -26,33 +26,90 @@
     clippy::cargo
 )]
 
+mod icons;
+#[cfg(any(feature = "2d", feature = "3d"))]
+mod layers;
 mod loader;
+mod markers;
 #[cfg(any(feature = "2d", feature = "3d"))]
 mod origin;
 #[cfg(any(feature = "2d", feature = "3d"))]
 mod plugin;
+#[cfg(any(feature = "2d", feature = "3d"))]
+mod removal;
 mod render;
 mod resources;
+#[cfg(any(feature = "2d", feature = "3d"))]
+mod scene;
+#[cfg(any(feature = "2d", feature = "3d"))]
+mod status;
+#[cfg(any(feature = "2d", feature = "3d"))]
+mod stroke;
+mod surface;
 mod svg;
+mod xml;
 
 /// Import this module as `use bevy_svg::prelude::*` to get convenient imports.
 pub mod prelude {
     pub use super::SvgPlugin;
     #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::layers::{SvgLayers, SvgParallaxLayer};
+    #[cfg(any(feature = "2d", feature = "3d"))]
     pub use crate::origin::Origin;
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::plugin::SvgSet;
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::removal::SvgRemovalPolicy;
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::scene::SvgPath;
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::status::{SvgEvent, SvgLoadFailed, SvgStatus};
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::stroke::{SvgStrokeScale, SvgStrokeWidth};
     #[cfg(feature = "2d")]
-    pub use crate::render::Svg2dBundle;
+    pub use crate::render::{Svg2d, Svg2dBundle};
     #[cfg(feature = "3d")]
-    pub use crate::render::Svg3dBundle;
-    pub use crate::svg::Svg;
+    pub use crate::render::{
+        Svg3d, Svg3dBundle, SvgBillboard, SvgBillboardMode, SvgDecal, SvgDecalMesh,
+    };
+    pub use crate::icons::SvgIconSheet;
+    pub use crate::render::{SvgVertexConstructor, SvgVertexContext};
+    pub use crate::loader::{
+        FileSvgError, HierarchyScene, LayerFilter, SvgError, SvgLoaderSettings,
+    };
+    pub use crate::markers::{MarkerSelector, MarkerShape, SvgMarker};
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub use crate::markers::{SvgMarkerInstance, SvgMarkerPrefabs};
+    pub use crate::surface::{SvgSurface, SvgWrap};
+    pub use crate::svg::{
+        DrawType, GroupDescriptor, LayerDepth, PaintOrder, PathDescriptor, PathSegment,
+        StrokeLineCap, StrokeLineJoin, StrokeStyle, Svg, Svg3dSettings, SvgAlphaMode,
+        SvgCullMode, SvgIndexAttribute, SvgLighting, SvgPathStyle, SvgStrokeUvMode,
+        SvgTessellation, SvgUvMode, SvgVertexFormat, ViewBox, ATTRIBUTE_COLOR_PACKED, ATTRIBUTE_PATH_INDEX, ATTRIBUTE_POSITION_2D,
+        ATTRIBUTE_STROKE_NORMAL, MAX_PATH_STYLES,
+    };
     pub use lyon_tessellation::{
-        FillOptions, FillRule, LineCap, LineJoin, Orientation, StrokeOptions,
+        FillOptions, FillRule, FillVertex, LineCap, LineJoin, Orientation, Side, StrokeOptions,
+        StrokeVertex,
     };
 }
 
+use std::collections::HashMap;
+
 #[cfg(any(feature = "2d", feature = "3d"))]
 use crate::plugin::SvgRenderPlugin;
-use crate::{loader::SvgAssetLoader, svg::Svg};
+use crate::{
+    icons::SvgIconSheet,
+    loader::{HierarchyScene, LayerFilter, SvgAssetLoader, SvgLoaderSettings},
+    markers::{MarkerShape, SvgMarker},
+    surface::SvgWrap,
+    svg::{
+        DrawType, GroupDescriptor, LayerDepth, PaintOrder, PathDescriptor, PathSegment,
+        StrokeLineCap, StrokeLineJoin, StrokeStyle, Svg, Svg3dSettings, SvgAlphaMode,
+        SvgCullMode, SvgIndexAttribute, SvgLighting, SvgPathStyle, SvgStrokeUvMode,
+        SvgTessellation, SvgUvMode, SvgVertexFormat, ViewBox,
+    },
+};
 use bevy::{
     app::{App, Plugin},
     asset::AssetApp,
@@ -63,13 +120,52 @@ pub struct SvgPlugin;
 
 impl Plugin for SvgPlugin {
     fn build(&self, app: &mut App) {
-        app.init_asset::<Svg>()
-            .init_asset_loader::<SvgAssetLoader>();
+        add_svg_assets(app);
         #[cfg(any(feature = "2d", feature = "3d"))]
         app.add_plugins(SvgRenderPlugin);
     }
 }
 
+/// Adds the [`Svg`] assets, their loader and all reflected types which don't need a renderer.
+fn add_svg_assets(app: &mut App) {
+    let loader = SvgAssetLoader::default();
+    app.init_asset::<Svg>()
+        .init_asset::<SvgIconSheet>()
+        .register_type::<SvgIconSheet>()
+        .register_type::<ViewBox>()
+        .register_type::<GroupDescriptor>()
+        .register_type::<LayerDepth>()
+        .register_type::<Option<f32>>()
+        .register_type::<Svg3dSettings>()
+        .register_type::<PaintOrder>()
+        .register_type::<SvgAlphaMode>()
+        .register_type::<SvgCullMode>()
+        .register_type::<SvgLighting>()
+        .register_type::<SvgWrap>()
+        .register_type::<Option<SvgWrap>>()
+        .register_type::<SvgTessellation>()
+        .register_type::<SvgUvMode>()
+        .register_type::<SvgStrokeUvMode>()
+        .register_type::<SvgIndexAttribute>()
+        .register_type::<SvgPathStyle>()
+        .register_type::<SvgVertexFormat>()
+        .register_type::<PathDescriptor>()
+        .register_type::<PathSegment>()
+        .register_type::<DrawType>()
+        .register_type::<StrokeStyle>()
+        .register_type::<StrokeLineCap>()
+        .register_type::<StrokeLineJoin>()
+        .register_type::<SvgMarker>()
+        .register_type::<MarkerShape>()
+        .register_type::<SvgLoaderSettings>()
+        .register_type::<LayerFilter>()
+        .register_type::<HierarchyScene>()
+        .register_type::<HashMap<String, LayerDepth>>()
+        .insert_resource(loader.failures())
+        .insert_resource(loader.settings())
+        .register_asset_loader(loader);
+}
+
 /// A locally defined [`std::convert::Into`] surrogate to overcome orphan rules.
 pub trait Convert<T>: Sized {
     /// Converts the value to `T`.
@@ -1,58 +1,187 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    ecs::system::Resource,
     log::debug,
+    reflect::{std_traits::ReflectDefault, Reflect},
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::svg::Svg;
+use crate::{
+    icons::SvgIconSheet,
+    svg::{LayerDepth, Svg, Svg3dSettings, SvgTessellation},
+};
+
+/// Settings of the [`Svg`] asset loader, use them with
+/// [`AssetServer::load_with_settings`](bevy::asset::AssetServer::load_with_settings).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub struct SvgLoaderSettings {
+    /// Which labeled [`Scene`](bevy::scene::Scene) the loader adds as `"Scene"`, e.g.
+    /// `"file.svg#Scene"`.
+    pub scene: HierarchyScene,
+    /// Whether the loader adds a labeled [`Svg`] and mesh for every group and path element
+    /// with an `id`, as `"Svg/<id>"` and `"mesh/<id>"`, e.g. `"level.svg#Svg/door"`.
+    ///
+    /// The symbols of an icon sheet are always added, as `"Icon/<id>"` and
+    /// `"Icon/<id>/mesh"`, see [`SvgIconSheet`].
+    ///
+    /// They are created with [`Svg::element`], so their labels stay the same when the file
+    /// is reloaded, as long as the ids don't change.
+    pub element_assets: bool,
+    /// Whether the loader adds a labeled [`Svg`] and mesh for every loaded layer, as
+    /// `"layer/<name>"` and `"layer/<name>/mesh"`, e.g. `"level.svg#layer/Background"`.
+    ///
+    /// They are created with [`Svg::layer`], see [`LayerFilter`] for the names of layers.
+    pub layer_assets: bool,
+    /// Which layers are loaded, all others are removed from the [`Svg`] and its mesh.
+    pub layers: LayerFilter,
+    /// The [`LayerDepth`] of layers by name, overriding the `data-depth` and
+    /// `data-parallax` attributes of the layers.
+    pub layer_depths: HashMap<String, LayerDepth>,
+    /// How the [`Svg`] and all of its labeled [`Svg`]s are rendered in 3D.
+    pub render_3d: Svg3dSettings,
+    /// How finely the [`Svg`] and all of its labeled [`Svg`]s are tessellated, e.g. to
+    /// deform them in a vertex shader.
+    pub tessellation: SvgTessellation,
+}
+
+/// Selects the layers of an [`Svg`] by name, see [`GroupDescriptor::layer`](crate::svg::GroupDescriptor::layer).
+///
+/// With [`SvgLoaderSettings::layer_assets`], every layer which is not excluded is also added
+/// as a labeled [`Svg`] and mesh, `"layer/<name>"` and `"layer/<name>/mesh"`, e.g.
+/// `"level.svg#layer/Background"`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum LayerFilter {
+    #[default]
+    /// All layers are loaded.
+    All,
+    /// Only the layers with these names are loaded.
+    Include(Vec<String>),
+    /// All layers except the ones with these names are loaded, e.g. guide or helper layers.
+    Exclude(Vec<String>),
+}
+
+impl LayerFilter {
+    /// Whether something inside of all the layers with the given names is loaded. Nested
+    /// layers are loaded, if one of them is included and none is excluded.
+    pub fn includes<'a>(&self, mut layers: impl Iterator<Item = &'a str>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Include(names) => layers.any(|layer| names.iter().any(|n| n == layer)),
+            Self::Exclude(names) => layers.all(|layer| names.iter().all(|n| n != layer)),
+        }
+    }
+}
+
+/// A [`Scene`](bevy::scene::Scene) with an entity hierarchy that mirrors the groups of an
+/// [`Svg`].
+///
+/// Every `<g>` becomes an entity with a [`Name`](bevy::core::Name) taken from its `id` and
+/// its [`Transform`](bevy::transform::components::Transform) relative to its parent. Every
+/// path element becomes a child entity of its group with its own [`Svg`] and mesh, so parts
+/// of the SVG can be moved, hidden or animated independently. The [`Origin`](crate::origin::Origin)
+/// is not supported for such a hierarchy, the top left corner of the SVG is at the position
+/// of the root entity.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum HierarchyScene {
+    #[default]
+    /// No scene is created.
+    None,
+    /// A scene with 2D SVG entities is created.
+    Svg2d,
+    /// A scene with 3D SVG entities is created.
+    Svg3d,
+}
 
 #[derive(Default)]
-pub struct SvgAssetLoader;
+pub struct SvgAssetLoader {
+    failures: SvgLoadFailures,
+    settings: SvgLoadedSettings,
+}
+
+impl SvgAssetLoader {
+    /// The errors of all failed loads of this loader.
+    pub(crate) fn failures(&self) -> SvgLoadFailures {
+        self.failures.clone()
+    }
+
+    /// The settings of all successful loads of this loader.
+    pub(crate) fn settings(&self) -> SvgLoadedSettings {
+        self.settings.clone()
+    }
+}
+
+/// The last error of every SVG file that failed to load, shared between the
+/// [`SvgAssetLoader`] and the systems that report failed loads.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct SvgLoadFailures(Arc<Mutex<HashMap<PathBuf, Arc<FileSvgError>>>>);
+
+impl SvgLoadFailures {
+    /// Returns the error of the last failed load of the file at `path`, if any.
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub(crate) fn get(&self, path: &std::path::Path) -> Option<Arc<FileSvgError>> {
+        self.0.lock().ok()?.get(path).cloned()
+    }
+
+    fn record(&self, path: &std::path::Path, result: &Result<Svg, Arc<FileSvgError>>) {
+        let Ok(mut failures) = self.0.lock() else {
+            return;
+        };
+        match result {
+            Ok(_) => failures.remove(path),
+            Err(err) => failures.insert(path.to_path_buf(), err.clone()),
+        };
+    }
+}
+
+/// The settings every SVG file was loaded with, shared between the [`SvgAssetLoader`] and
+/// the systems that record the [`SvgPath`](crate::scene::SvgPath) of entities.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct SvgLoadedSettings(Arc<Mutex<HashMap<PathBuf, SvgLoaderSettings>>>);
+
+impl SvgLoadedSettings {
+    /// Returns the settings of the last successful load of the file at `path`, if any.
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    pub(crate) fn get(&self, path: &std::path::Path) -> Option<SvgLoaderSettings> {
+        self.0.lock().ok()?.get(path).cloned()
+    }
+
+    fn record(&self, path: &std::path::Path, settings: &SvgLoaderSettings) {
+        if let Ok(mut loaded) = self.0.lock() {
+            loaded.insert(path.to_path_buf(), settings.clone());
+        }
+    }
+}
 
 impl AssetLoader for SvgAssetLoader {
     type Asset = Svg;
-    type Settings = ();
-    type Error = FileSvgError;
+    type Settings = SvgLoaderSettings;
+    type Error = Arc<FileSvgError>;
 
     fn load<'load>(
         &'load self,
         reader: &'load mut Reader,
-        _settings: &'load (),
+        settings: &'load SvgLoaderSettings,
         load_context: &'load mut LoadContext,
     ) -> BoxedFuture<'load, Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
-            debug!("Parsing SVG: {} ...", load_context.path().display());
-            let mut bytes = Vec::new();
-            reader
-                .read_to_end(&mut bytes)
+            let result = load_svg(reader, settings, load_context)
                 .await
-                .map_err(|e| FileSvgError {
-                    error: e.into(),
-                    path: load_context.path().display().to_string(),
-                })?;
-
-            let mut svg = Svg::from_bytes(&bytes, load_context.path(), None::<&std::path::Path>)?;
-            let name = &load_context
-                .path()
-                .file_name()
-                .ok_or_else(|| FileSvgError {
-                    error: SvgError::InvalidFileName(load_context.path().display().to_string()),
-                    path: load_context.path().display().to_string(),
-                })?
-                .to_string_lossy();
-            svg.name = name.to_string();
-            debug!("Parsing SVG: {} ... Done", load_context.path().display());
-
-            debug!("Tessellating SVG: {} ...", load_context.path().display());
-            let mesh = svg.tessellate();
-            debug!(
-                "Tessellating SVG: {} ... Done",
-                load_context.path().display()
-            );
-            let mesh_handle = load_context.add_labeled_asset("mesh".to_string(), mesh);
-            svg.mesh = mesh_handle;
-
-            Ok(svg)
+                .map_err(Arc::new);
+            self.failures.record(load_context.path(), &result);
+            if result.is_ok() {
+                self.settings.record(load_context.path(), settings);
+            }
+            result
         })
     }
 
@@ -61,23 +190,174 @@ impl AssetLoader for SvgAssetLoader {
     }
 }
 
-/// An error that occurs when loading a texture
+async fn load_svg<'load>(
+    reader: &'load mut Reader<'_>,
+    settings: &'load SvgLoaderSettings,
+    load_context: &'load mut LoadContext<'_>,
+) -> Result<Svg, FileSvgError> {
+    debug!("Parsing SVG: {} ...", load_context.path().display());
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| FileSvgError {
+            error: e.into(),
+            path: load_context.path().display().to_string(),
+        })?;
+
+    let (mut svg, symbols) =
+        Svg::with_symbols_from_bytes(&bytes, load_context.path(), None::<&std::path::Path>)?;
+    let name = &load_context
+        .path()
+        .file_name()
+        .ok_or_else(|| FileSvgError {
+            error: SvgError::InvalidFileName(load_context.path().display().to_string()),
+            path: load_context.path().display().to_string(),
+        })?
+        .to_string_lossy();
+    svg.name = name.to_string();
+    svg.render_3d = settings.render_3d;
+    svg.tessellation = settings.tessellation;
+    for group in &mut svg.groups {
+        let Some(depth) = group
+            .layer
+            .as_ref()
+            .and_then(|l| settings.layer_depths.get(l))
+        else {
+            continue;
+        };
+        group.depth = LayerDepth {
+            z: depth.z.or(group.depth.z),
+            parallax: depth.parallax.or(group.depth.parallax),
+        };
+    }
+    if settings.layers != LayerFilter::All {
+        svg.retain_layers(&settings.layers);
+    }
+    debug!("Parsing SVG: {} ... Done", load_context.path().display());
+
+    debug!("Tessellating SVG: {} ...", load_context.path().display());
+    let mesh = svg.tessellate();
+    debug!(
+        "Tessellating SVG: {} ... Done",
+        load_context.path().display()
+    );
+    let mesh_handle = load_context.add_labeled_asset("mesh".to_string(), mesh);
+    svg.mesh = mesh_handle;
+
+    if settings.element_assets {
+        debug!(
+            "Creating element assets of SVG: {} ...",
+            load_context.path().display()
+        );
+        for id in svg.element_ids() {
+            let Some(mut element) = svg.element(id) else {
+                continue;
+            };
+            element.mesh =
+                load_context.add_labeled_asset(format!("mesh/{id}"), element.tessellate());
+            load_context.add_labeled_asset(format!("Svg/{id}"), element);
+        }
+        debug!(
+            "Creating element assets of SVG: {} ... Done",
+            load_context.path().display()
+        );
+    }
+
+    if settings.layer_assets {
+        debug!(
+            "Creating layer assets of SVG: {} ...",
+            load_context.path().display()
+        );
+        for name in svg.layer_names() {
+            if !settings.layers.includes(std::iter::once(name)) {
+                continue;
+            }
+            let Some(mut layer) = svg.layer(name) else {
+                continue;
+            };
+            layer.mesh =
+                load_context.add_labeled_asset(format!("layer/{name}/mesh"), layer.tessellate());
+            load_context.add_labeled_asset(format!("layer/{name}"), layer);
+        }
+        debug!(
+            "Creating layer assets of SVG: {} ... Done",
+            load_context.path().display()
+        );
+    }
+
+    debug!(
+        "Creating icons of SVG: {} ...",
+        load_context.path().display()
+    );
+    if !symbols.is_empty() {
+        let mut sheet = SvgIconSheet::default();
+        for (id, mut icon) in symbols {
+            icon.render_3d = settings.render_3d;
+            icon.tessellation = settings.tessellation;
+            icon.mesh =
+                load_context.add_labeled_asset(format!("Icon/{id}/mesh"), icon.tessellate());
+            let handle = load_context.add_labeled_asset(format!("Icon/{id}"), icon);
+            sheet.icons.insert(id.clone(), handle);
+            sheet.ids.push(id);
+        }
+        load_context.add_labeled_asset("IconSheet".to_string(), sheet);
+    }
+    debug!(
+        "Creating icons of SVG: {} ... Done",
+        load_context.path().display()
+    );
+
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    if settings.scene != HierarchyScene::None {
+        debug!(
+            "Creating scene of SVG: {} ...",
+            load_context.path().display()
+        );
+        let scene = crate::scene::hierarchy_scene(&svg, settings.scene, load_context);
+        load_context.add_labeled_asset("Scene".to_string(), scene);
+        debug!(
+            "Creating scene of SVG: {} ... Done",
+            load_context.path().display()
+        );
+    }
+
+    Ok(svg)
+}
+
+/// An error that occurs when loading an SVG.
 #[derive(Error, Debug)]
 pub enum SvgError {
+    /// The file name of the SVG could not be determined.
     #[error("invalid file name")]
     InvalidFileName(String),
+    /// The SVG file could not be read.
     #[error("could not read file: {0}")]
     IoError(#[from] std::io::Error),
+    /// The SVG could not be parsed.
     #[error("failed to load an SVG: {0}")]
     SvgError(#[from] usvg::Error),
 }
 
-/// An error that occurs when loading a texture from a file.
+/// An error that occurs when loading an SVG from a file.
 #[derive(Error, Debug)]
 pub struct FileSvgError {
     pub(crate) error: SvgError,
     pub(crate) path: String,
 }
+
+impl FileSvgError {
+    /// The error that occurred.
+    pub const fn error(&self) -> &SvgError {
+        &self.error
+    }
+
+    /// The path of the file that failed to load.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
 impl std::fmt::Display for FileSvgError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         write!(
@@ -87,3 +367,201 @@ impl std::fmt::Display for FileSvgError {
         )
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        path::PathBuf,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use bevy::{
+        app::App,
+        asset::{Asset, AssetApp, AssetPlugin, AssetServer, Assets, Handle, LoadState},
+        ecs::system::Resource,
+        math::Vec2,
+        render::mesh::Mesh,
+        scene::ScenePlugin,
+        MinimalPlugins,
+    };
+
+    use super::*;
+
+    /// The directory the assets of a test app are loaded from.
+    #[derive(Resource)]
+    pub(crate) struct AssetDir(pub(crate) PathBuf);
+
+    /// Creates an app which loads SVGs from a new directory with the given files.
+    pub(crate) fn app(files: &[(&str, &str)]) -> App {
+        static DIRS: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "bevy_svg_test_{}_{}",
+            std::process::id(),
+            DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, content) in files {
+            std::fs::write(dir.join(name), content).unwrap();
+        }
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..Default::default()
+            },
+            ScenePlugin,
+        ))
+        .init_asset::<Mesh>()
+        .insert_resource(AssetDir(dir));
+        crate::add_svg_assets(&mut app);
+        app
+    }
+
+    /// Updates the app until the asset of `handle` and its dependencies are loaded.
+    pub(crate) fn wait_for<A: Asset>(app: &mut App, handle: &Handle<A>) {
+        let start = std::time::Instant::now();
+        while start.elapsed().as_secs() < 30 {
+            app.update();
+            let asset_server = app.world.resource::<AssetServer>();
+            if asset_server.is_loaded_with_dependencies(handle) {
+                return;
+            }
+            assert_ne!(asset_server.load_state(handle), LoadState::Failed);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("asset was not loaded in time");
+    }
+
+    /// Loads the file at `path` with `settings`.
+    pub(crate) fn load(app: &mut App, path: &str, settings: SvgLoaderSettings) -> Handle<Svg> {
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load_with_settings(path.to_string(), move |s: &mut SvgLoaderSettings| {
+                *s = settings.clone()
+            });
+        wait_for(app, &handle);
+        handle
+    }
+
+    #[test]
+    fn records_settings() {
+        let mut app = app(&[(
+            "a.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="5" height="5"/></svg>"#,
+        )]);
+        let settings = SvgLoaderSettings {
+            element_assets: true,
+            ..Default::default()
+        };
+        load(&mut app, "a.svg", settings.clone());
+        let loaded = app.world.resource::<SvgLoadedSettings>();
+        assert_eq!(
+            loaded.0.lock().unwrap().get(std::path::Path::new("a.svg")),
+            Some(&settings)
+        );
+    }
+
+    #[test]
+    fn layer_filter_includes_nested_layers() {
+        let include = LayerFilter::Include(vec!["Level".into()]);
+        assert!(include.includes(["Level"].into_iter()));
+        assert!(include.includes(["Doors", "Level"].into_iter()));
+        assert!(!include.includes(["Guides"].into_iter()));
+        assert!(!include.includes(std::iter::empty()));
+
+        let exclude = LayerFilter::Exclude(vec!["Guides".into()]);
+        assert!(exclude.includes(["Level"].into_iter()));
+        assert!(!exclude.includes(["Grid", "Guides"].into_iter()));
+        assert!(exclude.includes(std::iter::empty()));
+
+        assert!(LayerFilter::All.includes(["Guides"].into_iter()));
+    }
+
+    const LEVEL: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"
+        xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" viewBox="0 0 10 10">
+        <g inkscape:groupmode="layer" inkscape:label="Level">
+            <rect width="10" height="10"/>
+            <g inkscape:groupmode="layer" inkscape:label="Guides"><rect width="1" height="1"/></g>
+        </g>
+        <g inkscape:groupmode="layer" inkscape:label="Guides"><circle r="2"/></g>
+        <circle r="1"/>
+    </svg>"#;
+
+    /// Loads `level.svg` with `settings` and returns whether the labeled layer assets of
+    /// `Level` and `Guides` are loaded.
+    fn load_layer_assets(settings: SvgLoaderSettings) -> [bool; 2] {
+        let mut app = app(&[("level.svg", LEVEL)]);
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let file: Handle<Svg> =
+            asset_server.load_with_settings("level.svg", move |s: &mut SvgLoaderSettings| {
+                *s = settings.clone();
+            });
+        let layers = ["Level", "Guides"]
+            .map(|name| asset_server.load::<Svg>(format!("level.svg#layer/{name}")));
+        wait_for(&mut app, &file);
+        let svgs = app.world.resource::<Assets<Svg>>();
+        layers.map(|layer| svgs.contains(&layer))
+    }
+
+    #[test]
+    fn excluded_layers_are_not_loaded() {
+        let mut app = app(&[("level.svg", LEVEL)]);
+        let settings = SvgLoaderSettings {
+            layers: LayerFilter::Exclude(vec!["Guides".into()]),
+            ..Default::default()
+        };
+        let handle = load(&mut app, "level.svg", settings.clone());
+        let svg = app.world.resource::<Assets<Svg>>().get(&handle).unwrap();
+        // The rect of the level and the circle outside of any layer are kept
+        assert_eq!(svg.paths.len(), 2);
+
+        let settings = SvgLoaderSettings {
+            layer_assets: true,
+            ..settings
+        };
+        assert_eq!(load_layer_assets(settings), [true, false]);
+    }
+
+    #[test]
+    fn layer_assets_are_opt_in() {
+        assert_eq!(
+            load_layer_assets(SvgLoaderSettings::default()),
+            [false, false]
+        );
+        let settings = SvgLoaderSettings {
+            layer_assets: true,
+            ..Default::default()
+        };
+        assert_eq!(load_layer_assets(settings), [true, true]);
+    }
+
+    #[test]
+    fn icons_and_elements_have_separate_labels() {
+        let mut app = app(&[(
+            "sheet.svg",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <symbol id="close" viewBox="0 0 24 24"><rect width="24" height="24"/></symbol>
+                <rect id="close" width="50" height="40"/>
+            </svg>"#,
+        )]);
+        let settings = SvgLoaderSettings {
+            element_assets: true,
+            ..Default::default()
+        };
+        let _file = load(&mut app, "sheet.svg", settings);
+        let asset_server = app.world.resource::<AssetServer>();
+        let icon: Handle<Svg> = asset_server.load("sheet.svg#Icon/close");
+        let element: Handle<Svg> = asset_server.load("sheet.svg#Svg/close");
+        wait_for(&mut app, &icon);
+        wait_for(&mut app, &element);
+
+        let svgs = app.world.resource::<Assets<Svg>>();
+        let icon = svgs.get(&icon).unwrap();
+        assert_eq!(icon.name, "sheet.svg#Icon/close");
+        assert_eq!(icon.size, Vec2::new(24.0, 24.0));
+        assert_eq!(svgs.get(&element).unwrap().size, Vec2::new(50.0, 40.0));
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+#[cfg(any(feature = "2d", feature = "3d"))]
+use std::sync::Arc;
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+use bevy::{
+    asset::Assets,
+    core::Name,
+    ecs::{
+        component::Component,
+        event::EventReader,
+        query::With,
+        reflect::ReflectComponent,
+        system::{Commands, EntityCommands, Query, Res, Resource},
+    },
+    hierarchy::{BuildChildren, Children, DespawnRecursiveExt},
+    render::prelude::SpatialBundle,
+};
+use bevy::{
+    math::{Rect, Vec2, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    transform::components::Transform,
+};
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+use crate::{origin::Origin, status::SvgEvent, svg::Svg};
+use crate::{svg::PathSegment, xml::ElementAttributes};
+
+/// A shape of an SVG which marks a location in a level, like a spawn point, a trigger area
+/// or a patrol route, see [`Svg::markers`](crate::svg::Svg::markers).
+///
+/// Markers are created from every `<circle>`, `<ellipse>`, `<rect>`, `<line>`, `<polyline>`
+/// and `<polygon>` element with an `id`, whether it is painted or not. Elements hidden with
+/// `display="none"` are removed while parsing, so they can't be markers.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default, Debug)]
+pub struct SvgMarker {
+    /// The `id` attribute of the element.
+    pub id: String,
+    /// The entries of the `class` attribute of the element.
+    pub class: Vec<String>,
+    /// The `data-*` attributes of the element, without the `data-` prefix.
+    pub data: HashMap<String, String>,
+    /// The geometry of the marker.
+    pub shape: MarkerShape,
+}
+
+/// The geometry of a [`SvgMarker`].
+///
+/// All coordinates are in the coordinate system of the SVG mesh, i.e. the SVG user space
+/// with the same flipped y-axis as the tessellated mesh, so a marker lines up with the
+/// rendered SVG when it is placed relative to the SVG entity.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[reflect(Default, Debug)]
+pub enum MarkerShape {
+    /// The center of a `<circle>` or `<ellipse>`.
+    Point(Vec2),
+    /// The bounding box of a `<rect>`.
+    Area(Rect),
+    /// The points of a `<line>`, `<polyline>` or `<polygon>`.
+    Path {
+        /// The points in document order.
+        points: Vec<Vec2>,
+        /// Whether the last point is connected to the first one, which is the case for
+        /// a `<polygon>`.
+        closed: bool,
+    },
+}
+
+impl Default for MarkerShape {
+    fn default() -> Self {
+        Self::Point(Vec2::ZERO)
+    }
+}
+
+impl MarkerShape {
+    /// The location of the shape: the point, the center of the area or the first point of
+    /// the path.
+    pub fn position(&self) -> Vec2 {
+        match self {
+            Self::Point(point) => *point,
+            Self::Area(rect) => rect.center(),
+            Self::Path { points, .. } => points.first().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Selects [`SvgMarker`]s by their id or `data-*` attributes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MarkerSelector {
+    /// Markers whose id starts with the prefix, e.g. `"spawn_"` selects `spawn_player`.
+    IdPrefix(String),
+    /// Markers with the `data-*` attribute, given without the `data-` prefix.
+    Data(String),
+    /// Markers whose `data-*` attribute, given without the `data-` prefix, has the value.
+    DataValue(String, String),
+}
+
+impl MarkerSelector {
+    /// Whether `marker` is selected.
+    pub fn matches(&self, marker: &SvgMarker) -> bool {
+        match self {
+            Self::IdPrefix(prefix) => marker.id.starts_with(prefix.as_str()),
+            Self::Data(key) => marker.data.contains_key(key),
+            Self::DataValue(key, value) => marker.data.get(key) == Some(value),
+        }
+    }
+}
+
+impl SvgMarker {
+    /// Creates the marker of a path element, if the element is a marker shape.
+    pub(crate) fn from_path(
+        id: &str,
+        attributes: &ElementAttributes,
+        segments: &[PathSegment],
+        abs_transform: Transform,
+    ) -> Option<Self> {
+        // Bevy has a different y-axis origin, so the same flip as for the mesh is applied
+        let transform = Transform::from_scale(Vec3::new(1.0, -1.0, 1.0)) * abs_transform;
+        let points: Vec<Vec2> = segments
+            .iter()
+            .filter_map(|segment| match *segment {
+                PathSegment::Begin { at } => Some(at),
+                PathSegment::Line { to, .. }
+                | PathSegment::Quadratic { to, .. }
+                | PathSegment::Cubic { to, .. } => Some(to),
+                PathSegment::End { .. } => None,
+            })
+            .map(|point| (transform * point.extend(0.0)).truncate())
+            .collect();
+        let bounds = || {
+            points
+                .iter()
+                .map(|point| Rect::from_corners(*point, *point))
+                .reduce(|bounds, rect| bounds.union(rect))
+        };
+
+        let shape = match attributes.tag.as_str() {
+            "circle" | "ellipse" => MarkerShape::Point(bounds()?.center()),
+            "rect" => MarkerShape::Area(bounds()?),
+            "line" | "polyline" | "polygon" => MarkerShape::Path {
+                closed: attributes.tag == "polygon",
+                points,
+            },
+            _ => return None,
+        };
+
+        Some(Self {
+            id: id.to_string(),
+            class: attributes.class.clone(),
+            data: attributes.data.clone(),
+            shape,
+        })
+    }
+}
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+type SpawnMarker = dyn Fn(&mut EntityCommands<'_, '_, '_>, &SvgMarker) + Send + Sync;
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+/// Prefabs which are spawned for the [`SvgMarker`]s of every SVG entity.
+///
+/// When an SVG entity receives the mesh of its [`Svg`], a child entity is spawned for every
+/// marker which is selected by a prefab. If the [`MarkerSelector`]s of several prefabs match
+/// a marker, only the prefab which was added first is spawned for it. The child has a
+/// [`Name`] taken from the marker id, a [`SpatialBundle`] at the
+/// [`position`](MarkerShape::position) of the marker and a [`SvgMarkerInstance`], then the
+/// spawn function of the prefab adds its own components. When the [`Svg`] is reloaded, the
+/// children are despawned and spawned again.
+///
+/// The [`Origin`] of the SVG entity at that time is taken into account, later changes of
+/// the [`Origin`] don't move the children.
+///
+/// ```rust,ignore
+/// app.world
+///     .resource_mut::<SvgMarkerPrefabs>()
+///     .add(MarkerSelector::IdPrefix("spawn_".into()), |entity, _marker| {
+///         entity.insert(SpawnPoint);
+///     });
+/// ```
+#[derive(Clone, Default, Resource)]
+pub struct SvgMarkerPrefabs {
+    prefabs: Vec<(MarkerSelector, Arc<SpawnMarker>)>,
+}
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+impl SvgMarkerPrefabs {
+    /// Adds a prefab, `spawn` is called with the spawned child of every selected marker
+    /// which is not selected by a prefab added before.
+    pub fn add(
+        &mut self,
+        selector: MarkerSelector,
+        spawn: impl Fn(&mut EntityCommands<'_, '_, '_>, &SvgMarker) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.prefabs.push((selector, Arc::new(spawn)));
+        self
+    }
+}
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+#[derive(Clone, Component, Debug, Default, Eq, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// Added to every child entity spawned by a prefab of [`SvgMarkerPrefabs`], with the id of
+/// its [`SvgMarker`].
+pub struct SvgMarkerInstance(pub String);
+
+#[cfg(any(feature = "2d", feature = "3d"))]
+/// Spawns the prefabs of all markers of SVG entities which received a new mesh.
+pub(crate) fn spawn_svg_markers(
+    mut commands: Commands,
+    prefabs: Res<SvgMarkerPrefabs>,
+    svgs: Res<Assets<Svg>>,
+    mut svg_events: EventReader<SvgEvent>,
+    query: Query<(Option<&Children>, Option<&Origin>)>,
+    instances: Query<(), With<SvgMarkerInstance>>,
+) {
+    if prefabs.prefabs.is_empty() {
+        svg_events.clear();
+        return;
+    }
+
+    for event in svg_events.read() {
+        let (SvgEvent::Ready { entity, handle } | SvgEvent::Reloaded { entity, handle }) = event;
+        let (Some(svg), Ok((children, origin))) = (svgs.get(handle), query.get(*entity)) else {
+            continue;
+        };
+
+        for child in children.into_iter().flatten() {
+            if instances.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        let offset = origin.map_or(Vec3::ZERO, |origin| origin.compute_translation(svg.size));
+        for marker in &svg.markers {
+            let Some((_, spawn)) = prefabs
+                .prefabs
+                .iter()
+                .find(|(selector, _)| selector.matches(marker))
+            else {
+                continue;
+            };
+            let transform =
+                Transform::from_translation(marker.shape.position().extend(0.0) + offset);
+            let mut child = commands.spawn((
+                Name::new(marker.id.clone()),
+                SpatialBundle::from_transform(transform),
+                SvgMarkerInstance(marker.id.clone()),
+            ));
+            child.set_parent(*entity);
+            spawn(&mut child, marker);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    use bevy::{
+        app::{App, Update},
+        asset::Handle,
+        ecs::entity::Entity,
+    };
+
+    use super::*;
+    use crate::svg::Svg;
+
+    #[test]
+    fn markers_are_created_from_shapes_with_id() {
+        let svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <circle id="spawn_player" cx="10" cy="20" r="5" data-team="red"/>
+                <g transform="translate(50, 0)">
+                    <rect id="trigger" class="area door" x="0" y="10" width="20" height="10"
+                        fill="none"/>
+                </g>
+                <polygon id="patrol" points="0,0 10,0 10,10"/>
+                <path id="not_a_marker" d="M0 0 L10 10"/>
+                <circle cx="1" cy="1" r="1"/>
+            </svg>"#,
+            "level.svg",
+            None::<&str>,
+        )
+        .unwrap();
+
+        let ids: Vec<_> = svg
+            .markers
+            .iter()
+            .map(|marker| marker.id.as_str())
+            .collect();
+        assert_eq!(ids, ["spawn_player", "trigger", "patrol"]);
+
+        // The y-axis is flipped like the mesh
+        let spawn = &svg.markers[0];
+        assert_eq!(spawn.shape, MarkerShape::Point(Vec2::new(10.0, -20.0)));
+        assert_eq!(spawn.data.get("team").map(String::as_str), Some("red"));
+
+        let trigger = &svg.markers[1];
+        assert_eq!(trigger.class, ["area", "door"]);
+        assert_eq!(
+            trigger.shape,
+            MarkerShape::Area(Rect::new(50.0, -10.0, 70.0, -20.0))
+        );
+        assert_eq!(trigger.shape.position(), Vec2::new(60.0, -15.0));
+
+        let MarkerShape::Path { points, closed } = &svg.markers[2].shape else {
+            panic!("expected a path");
+        };
+        assert!(*closed);
+        assert_eq!(
+            points[..3],
+            [Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, -10.0)]
+        );
+    }
+
+    #[test]
+    fn selectors_match_ids_and_data() {
+        let marker = SvgMarker {
+            id: "spawn_player".into(),
+            data: HashMap::from([("team".into(), "red".into())]),
+            ..Default::default()
+        };
+        assert!(MarkerSelector::IdPrefix("spawn_".into()).matches(&marker));
+        assert!(!MarkerSelector::IdPrefix("player".into()).matches(&marker));
+        assert!(MarkerSelector::Data("team".into()).matches(&marker));
+        assert!(!MarkerSelector::Data("spawn".into()).matches(&marker));
+        assert!(MarkerSelector::DataValue("team".into(), "red".into()).matches(&marker));
+        assert!(!MarkerSelector::DataValue("team".into(), "blue".into()).matches(&marker));
+    }
+
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    #[derive(Component)]
+    struct Prefab(&'static str);
+
+    /// Spawns an SVG entity with two markers and the prefabs of their [`SvgEvent::Ready`].
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    fn spawn_markers(prefabs: SvgMarkerPrefabs) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<SvgEvent>()
+            .init_resource::<Assets<Svg>>()
+            .insert_resource(prefabs)
+            .add_systems(Update, spawn_svg_markers);
+        let svg = Svg {
+            markers: vec![
+                SvgMarker {
+                    id: "spawn_player".into(),
+                    data: HashMap::from([("team".into(), "red".into())]),
+                    shape: MarkerShape::Point(Vec2::new(1.0, 2.0)),
+                    ..Default::default()
+                },
+                SvgMarker {
+                    id: "spawn_enemy".into(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let handle = app.world.resource_mut::<Assets<Svg>>().add(svg);
+        let entity = app.world.spawn(handle.clone()).id();
+        app.world.send_event(SvgEvent::Ready { entity, handle });
+        app.update();
+        (app, entity)
+    }
+
+    /// Returns the marker id and prefab of every child of `entity`.
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    fn instances(app: &App, entity: Entity) -> Vec<(&str, &str)> {
+        app.world
+            .get::<Children>(entity)
+            .into_iter()
+            .flatten()
+            .map(|child| {
+                let child = app.world.entity(*child);
+                (
+                    child.get::<SvgMarkerInstance>().unwrap().0.as_str(),
+                    child.get::<Prefab>().unwrap().0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    fn first_matching_prefab_is_spawned() {
+        let mut prefabs = SvgMarkerPrefabs::default();
+        prefabs
+            .add(MarkerSelector::Data("team".into()), |child, _| {
+                child.insert(Prefab("team"));
+            })
+            .add(MarkerSelector::IdPrefix("spawn_".into()), |child, _| {
+                child.insert(Prefab("spawn"));
+            });
+        let (app, entity) = spawn_markers(prefabs);
+        assert_eq!(
+            instances(&app, entity),
+            [("spawn_player", "team"), ("spawn_enemy", "spawn")]
+        );
+        let child = app.world.get::<Children>(entity).unwrap()[0];
+        assert_eq!(
+            app.world.get::<Transform>(child),
+            Some(&Transform::from_xyz(1.0, 2.0, 0.0))
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "2d", feature = "3d"))]
+    fn prefabs_are_respawned_on_reload() {
+        let mut prefabs = SvgMarkerPrefabs::default();
+        prefabs.add(MarkerSelector::IdPrefix("spawn_".into()), |child, _| {
+            child.insert(Prefab("spawn"));
+        });
+        let (mut app, entity) = spawn_markers(prefabs);
+        let handle = app.world.get::<Handle<Svg>>(entity).unwrap().clone();
+        app.world.send_event(SvgEvent::Reloaded { entity, handle });
+        app.update();
+        assert_eq!(
+            instances(&app, entity),
+            [("spawn_player", "spawn"), ("spawn_enemy", "spawn")]
+        );
+    }
+}
@@ -1,13 +1,16 @@
 use bevy::{
     asset::{Assets, Handle},
     ecs::{
+        change_detection::DetectChanges,
         component::Component,
         entity::Entity,
         query::{Changed, Or, With, Without},
+        reflect::ReflectComponent,
         system::{Commands, Query, Res},
     },
     math::{Vec2, Vec3, Vec3Swizzles},
-    transform::components::{GlobalTransform, Transform},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    transform::components::GlobalTransform,
 };
 
 #[cfg(feature = "3d")]
@@ -17,7 +20,8 @@ use bevy::sprite::Mesh2dHandle;
 
 use crate::svg::Svg;
 
-#[derive(Clone, Component, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Component, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug)]
 /// Origin of the coordinate system.
 pub enum Origin {
     /// Bottom left of the image or viewbox.
@@ -38,19 +42,21 @@ impl Origin {
     /// to the translation of the SVG.
     pub fn compute_translation(&self, scaled_size: Vec2) -> Vec3 {
         match self {
-            Origin::BottomLeft => Vec3::new(0.0, scaled_size.y, 0.0),
-            Origin::BottomRight => Vec3::new(-scaled_size.x, scaled_size.y, 0.0),
-            Origin::Center => Vec3::new(-scaled_size.x * 0.5, scaled_size.y * 0.5, 0.0),
+            Self::BottomLeft => Vec3::new(0.0, scaled_size.y, 0.0),
+            Self::BottomRight => Vec3::new(-scaled_size.x, scaled_size.y, 0.0),
+            Self::Center => Vec3::new(-scaled_size.x * 0.5, scaled_size.y * 0.5, 0.0),
             // Standard SVG origin is top left, so we don't need to do anything
-            Origin::TopLeft => Vec3::ZERO,
-            Origin::TopRight => Vec3::new(-scaled_size.x, 0.0, 0.0),
+            Self::TopLeft => Vec3::ZERO,
+            Self::TopRight => Vec3::new(-scaled_size.x, 0.0, 0.0),
         }
     }
 }
 
-#[derive(Clone, Component, Copy, Debug, PartialEq)]
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
 pub(crate) struct OriginState {
-    previous: Origin,
+    /// The offset which was added to the [`GlobalTransform`] of the entity.
+    offset: Vec3,
 }
 
 #[cfg(feature = "2d")]
@@ -62,16 +68,14 @@ type WithMesh = With<Handle<Mesh>>;
 #[cfg(all(feature = "2d", feature = "3d"))]
 type WithMesh = Or<(With<Mesh2dHandle>, With<Handle<Mesh>>)>;
 
-/// Checkes if a "new" SVG bundle was added by looking for a missing OriginState
+/// Checkes if a "new" SVG bundle was added by looking for a missing `OriginState`
 /// and then adds it to the entity.
 pub(crate) fn add_origin_state(
     mut commands: Commands,
     query: Query<Entity, (With<Handle<Svg>>, WithMesh, Without<OriginState>)>,
 ) {
     for entity in &query {
-        commands.entity(entity).insert(OriginState {
-            previous: Origin::default(),
-        });
+        commands.entity(entity).insert(OriginState::default());
     }
 }
 
@@ -84,57 +88,139 @@ type ChangedMesh = Changed<Handle<Mesh>>;
 #[cfg(all(feature = "2d", feature = "3d"))]
 type ChangedMesh = Or<(Changed<Mesh2dHandle>, Changed<Handle<Mesh>>)>;
 
-/// Gets all SVGs with a changed origin or transform and checks if the origin offset
-/// needs to be applied.
+/// Gets all SVGs with a changed origin, global transform or mesh and applies the offset of
+/// their origin to their [`GlobalTransform`].
+///
+/// A [`GlobalTransform`] which was changed by the transform propagation holds no offset, so the
+/// full offset of the current origin is added to it. Otherwise the offset which was applied
+/// before is replaced.
 pub(crate) fn apply_origin(
     svgs: Res<Assets<Svg>>,
     mut query: Query<
         (
-            Entity,
             &Handle<Svg>,
             &Origin,
             &mut OriginState,
-            &Transform,
-            Changed<Transform>,
             &mut GlobalTransform,
         ),
-        Or<(Changed<Origin>, Changed<Transform>, ChangedMesh)>,
+        Or<(Changed<Origin>, Changed<GlobalTransform>, ChangedMesh)>,
     >,
 ) {
-    for (
-        _,
-        svg_handle,
-        origin,
-        mut origin_state,
-        transform,
-        transform_changed,
-        mut global_transform,
-    ) in &mut query
-    {
-        if let Some(svg) = svgs.get(svg_handle) {
-            if origin_state.previous != *origin {
-                let scaled_size = svg.size * transform.scale.xy();
-                let reverse_origin_translation =
-                    origin_state.previous.compute_translation(scaled_size);
-                let origin_translation = origin.compute_translation(scaled_size);
-
-                let mut gtransf = global_transform.compute_transform();
-                gtransf.translation.x += origin_translation.x - reverse_origin_translation.x;
-                gtransf.translation.y += origin_translation.y - reverse_origin_translation.y;
-                gtransf.translation.z += origin_translation.z - reverse_origin_translation.z;
-                *global_transform = GlobalTransform::from(gtransf);
-
-                origin_state.previous = origin.clone();
-            } else if transform_changed {
-                let scaled_size = svg.size * transform.scale.xy();
-                let origin_translation = origin.compute_translation(scaled_size);
-
-                let mut gtransf = global_transform.compute_transform();
-                gtransf.translation.x += origin_translation.x;
-                gtransf.translation.y += origin_translation.y;
-                gtransf.translation.z += origin_translation.z;
-                *global_transform = GlobalTransform::from(gtransf);
+    for (svg_handle, origin, mut origin_state, mut global_transform) in &mut query {
+        let propagated = global_transform.is_changed();
+        let Some(svg) = svgs.get(svg_handle) else {
+            if propagated {
+                origin_state.offset = Vec3::ZERO;
             }
+            continue;
+        };
+
+        let mut transform = global_transform.compute_transform();
+        let offset = origin.compute_translation(svg.size * transform.scale.xy());
+        if !propagated {
+            transform.translation -= origin_state.offset;
         }
+        transform.translation += offset;
+        *global_transform = GlobalTransform::from(transform);
+        origin_state.offset = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, PostUpdate},
+        ecs::{entity::Entity, schedule::IntoSystemConfigs},
+        hierarchy::BuildWorldChildren,
+        math::Vec3,
+        transform::{
+            components::{GlobalTransform, Transform},
+            TransformPlugin, TransformSystem,
+        },
+    };
+
+    use super::*;
+
+    fn app() -> (App, Handle<Svg>) {
+        let mut app = App::new();
+        app.add_plugins(TransformPlugin)
+            .init_resource::<Assets<Svg>>()
+            .add_systems(
+                PostUpdate,
+                apply_origin.after(TransformSystem::TransformPropagate),
+            );
+        let svg = app.world.resource_mut::<Assets<Svg>>().add(Svg {
+            size: Vec2::new(100.0, 50.0),
+            ..Default::default()
+        });
+        (app, svg)
+    }
+
+    fn spawn(app: &mut App, svg: &Handle<Svg>, origin: Origin) -> Entity {
+        app.world
+            .spawn((
+                svg.clone(),
+                origin,
+                OriginState::default(),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .id()
+    }
+
+    fn translation(app: &App, entity: Entity) -> Vec3 {
+        app.world
+            .get::<GlobalTransform>(entity)
+            .unwrap()
+            .translation()
+    }
+
+    #[test]
+    fn transform_and_origin_changed_in_same_frame() {
+        let (mut app, svg) = app();
+        let entity = spawn(&mut app, &svg, Origin::TopLeft);
+        app.update();
+        assert_eq!(translation(&app, entity), Vec3::ZERO);
+
+        let mut entity_mut = app.world.entity_mut(entity);
+        entity_mut.get_mut::<Transform>().unwrap().translation.x = 10.0;
+        *entity_mut.get_mut::<Origin>().unwrap() = Origin::Center;
+        app.update();
+        assert_eq!(translation(&app, entity), Vec3::new(-40.0, 25.0, 0.0));
+
+        // Without any other change the offset is kept
+        app.update();
+        assert_eq!(translation(&app, entity), Vec3::new(-40.0, 25.0, 0.0));
+
+        // Only the origin changed, the previous offset is replaced
+        *app.world.get_mut::<Origin>(entity).unwrap() = Origin::BottomRight;
+        app.update();
+        assert_eq!(translation(&app, entity), Vec3::new(-90.0, 50.0, 0.0));
+    }
+
+    #[test]
+    fn parent_moved() {
+        let (mut app, svg) = app();
+        let child = spawn(&mut app, &svg, Origin::Center);
+        let parent = app
+            .world
+            .spawn((Transform::default(), GlobalTransform::default()))
+            .push_children(&[child])
+            .id();
+        app.update();
+        assert_eq!(translation(&app, child), Vec3::new(-50.0, 25.0, 0.0));
+
+        app.world.get_mut::<Transform>(parent).unwrap().translation = Vec3::new(5.0, 5.0, 0.0);
+        app.update();
+        assert_eq!(translation(&app, child), Vec3::new(-45.0, 30.0, 0.0));
+    }
+
+    #[test]
+    fn offset_scales_with_entity() {
+        let (mut app, svg) = app();
+        let entity = spawn(&mut app, &svg, Origin::Center);
+        app.world.get_mut::<Transform>(entity).unwrap().scale = Vec3::splat(2.0);
+        app.update();
+        assert_eq!(translation(&app, entity), Vec3::new(-100.0, 50.0, 0.0));
     }
 }
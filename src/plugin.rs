@@ -1,45 +1,96 @@
 //! Contains the plugin and its helper types.
 //!
-//! The [`Svg2dBundle`](crate::bundle::Svg2dBundle) provides a way to display an `SVG`-file
+//! The [`Svg2dBundle`](crate::render::Svg2dBundle) provides a way to display an `SVG`-file
 //! with minimal boilerplate.
 //!
 //! ## How it works
-//! The user creates/loades a [`Svg2dBundle`](crate::bundle::Svg2dBundle) in a system.
+//! The user creates/loades a [`Svg2dBundle`](crate::render::Svg2dBundle) in a system.
 //!
-//! Then, in the [`Set::SVG`](Set::SVG), a mesh is created for each loaded [`Svg`] bundle.
+//! Then, in [`SvgSet::LinkMeshes`], the mesh of each loaded [`Svg`] is added to its bundle
+//! and afterwards, in [`SvgSet::ApplyOrigin`], the [`Origin`] is applied.
+//! Both sets run in [`PostUpdate`], so changes take effect in the frame they are made.
 //! Each mesh is then extracted in the [`RenderSet::Extract`](bevy::render::RenderSet) and added to the
 //! [`RenderWorld`](bevy::render::RenderWorld).
 //! Afterwards it is queued in the [`RenderSet::Queue`](bevy::render::RenderSet) for actual drawing/rendering.
 
-#[cfg(feature = "3d")]
-use std::ops::Deref;
+use std::collections::HashSet;
 
 use bevy::{
     app::{App, Plugin},
-    asset::{AssetEvent, Assets, Handle},
+    asset::{AssetEvent, AssetId, Assets, Handle},
     ecs::{
+        change_detection::DetectChangesMut,
         entity::Entity,
-        event::EventReader,
-        query::{Added, Changed, Or},
-        schedule::{IntoSystemConfigs, SystemSet},
-        system::{Commands, Query, Res, ResMut},
+        event::{EventReader, EventWriter},
+        query::{Added, Changed, Has, Or, With, Without},
+        schedule::{apply_deferred, IntoSystemConfigs, IntoSystemSetConfigs, SystemSet},
+        system::{Commands, Query, Res, ResMut, SystemParam},
     },
     hierarchy::DespawnRecursiveExt,
     log::debug,
-    prelude::{Last, PostUpdate},
-    render::mesh::Mesh,
+    prelude::PostUpdate,
+    render::{
+        mesh::Mesh,
+        view::{InheritedVisibility, ViewVisibility, Visibility, VisibilitySystems},
+    },
+    transform::{
+        components::{GlobalTransform, Transform},
+        TransformSystem,
+    },
 };
 
 #[cfg(feature = "2d")]
 use bevy::sprite::Mesh2dHandle;
 
-use crate::{origin, render, svg::Svg};
+#[cfg(all(feature = "2d", feature = "3d"))]
+use crate::render::Svg3d;
+
+use crate::{
+    layers::{self, SvgLayers, SvgParallaxLayer},
+    markers::{self, SvgMarkerInstance, SvgMarkerPrefabs},
+    origin::{self, Origin, OriginState},
+    removal::SvgRemovalPolicy,
+    render,
+    scene::{self, SvgPath},
+    status::{self, SvgEvent, SvgLoadFailed, SvgStatus},
+    stroke::{SvgStrokeScale, SvgStrokeWidth},
+    svg::Svg,
+};
 
-/// Sets for this plugin.
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
-pub enum Set {
-    /// Set in which [`Svg2dBundle`](crate::bundle::Svg2dBundle)s get drawn.
-    SVG,
+/// System sets of this plugin, they all run in the [`PostUpdate`] schedule.
+///
+/// Order your own systems relative to these sets, if they need to observe or change
+/// the meshes or origins of SVG entities in the same frame.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, SystemSet)]
+pub enum SvgSet {
+    /// Adds the [`Mesh`] of an [`Svg`] to the entities using it, when the [`Svg`] has been
+    /// loaded or modified, or the [`Handle<Svg>`] of an entity changed. Updates the
+    /// [`SvgStatus`] of these entities and sends the corresponding [`SvgEvent`]s and
+    /// [`SvgLoadFailed`] events. Spawns the prefabs of [`SvgMarkerPrefabs`] for the
+    /// markers of these entities.
+    ///
+    /// Entities with a [`SvgPath`] get the [`Handle<Svg>`] of that path and entities
+    /// which only have a [`Handle<Svg>`] get all missing components of a
+    /// [`Svg2dBundle`](crate::render::Svg2dBundle) or [`Svg3dBundle`](crate::render::Svg3dBundle)
+    /// added beforehand, as well as the children of new or reloaded [`SvgLayers`].
+    ///
+    /// Runs before [`TransformSystem::TransformPropagate`] and
+    /// [`VisibilitySystems::CalculateBounds`], so new meshes are rendered and culled with
+    /// the correct bounds in the same frame. All commands issued in this set are applied
+    /// at its end.
+    LinkMeshes,
+    /// Moves every [`SvgParallaxLayer`] by its parallax relative to the active camera.
+    ///
+    /// Runs after [`SvgSet::LinkMeshes`] and before
+    /// [`TransformSystem::TransformPropagate`], so move the camera before this set to avoid
+    /// the layers lagging one frame behind.
+    ApplyParallax,
+    /// Applies the [`Origin`] of each SVG entity to its [`GlobalTransform`](bevy::transform::components::GlobalTransform).
+    ///
+    /// Runs after [`TransformSystem::TransformPropagate`], because that would overwrite the
+    /// applied offset, and before [`VisibilitySystems::CheckVisibility`], so the SVG is
+    /// culled and rendered at its final position in the same frame.
+    ApplyOrigin,
 }
 
 /// A plugin that makes sure your [`Svg`]s get rendered
@@ -47,15 +98,165 @@ pub struct SvgRenderPlugin;
 
 impl Plugin for SvgRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, (origin::add_origin_state.in_set(Set::SVG),))
+        app.register_type::<Origin>()
+            .register_type::<OriginState>()
+            .register_type::<SvgPath>()
+            .register_type::<SvgRemovalPolicy>()
+            .register_type::<SvgStatus>()
+            .register_type::<SvgMarkerInstance>()
+            .register_type::<SvgLayers>()
+            .register_type::<SvgParallaxLayer>()
+            .register_type::<SvgStrokeWidth>()
+            .register_type::<SvgStrokeScale>()
+            .init_resource::<SvgRemovalPolicy>()
+            .init_resource::<SvgMarkerPrefabs>()
+            .add_event::<SvgEvent>()
+            .add_event::<SvgLoadFailed>()
+            .configure_sets(
+                PostUpdate,
+                (
+                    SvgSet::LinkMeshes
+                        .before(TransformSystem::TransformPropagate)
+                        .before(VisibilitySystems::CalculateBounds),
+                    SvgSet::ApplyParallax
+                        .after(SvgSet::LinkMeshes)
+                        .before(TransformSystem::TransformPropagate),
+                    SvgSet::ApplyOrigin
+                        .after(SvgSet::LinkMeshes)
+                        .after(TransformSystem::TransformPropagate)
+                        .before(VisibilitySystems::CheckVisibility),
+                ),
+            )
             .add_systems(
-                Last,
-                (origin::apply_origin, svg_mesh_linker.in_set(Set::SVG)),
+                PostUpdate,
+                (
+                    (
+                        scene::load_svg_paths,
+                        scene::record_svg_paths,
+                        layers::spawn_svg_layers,
+                        apply_deferred,
+                        add_svg_components,
+                        apply_deferred,
+                        origin::add_origin_state,
+                        svg_mesh_linker,
+                        markers::spawn_svg_markers,
+                        apply_deferred,
+                        status::detect_failed_loads,
+                    )
+                        .chain()
+                        .in_set(SvgSet::LinkMeshes),
+                    layers::apply_parallax.in_set(SvgSet::ApplyParallax),
+                    origin::apply_origin.in_set(SvgSet::ApplyOrigin),
+                ),
             )
             .add_plugins(render::SvgPlugin);
     }
 }
 
+/// Returns the meshes which were modified, e.g. because their file was reloaded, according
+/// to the unread `events`.
+pub(crate) fn changed_meshes(events: &mut EventReader<AssetEvent<Mesh>>) -> HashSet<AssetId<Mesh>> {
+    events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(feature = "2d")]
+#[cfg(not(feature = "3d"))]
+type WithoutMesh = Without<Mesh2dHandle>;
+#[cfg(not(feature = "2d"))]
+#[cfg(feature = "3d")]
+type WithoutMesh = Without<Handle<Mesh>>;
+#[cfg(all(feature = "2d", feature = "3d"))]
+type WithoutMesh = (Without<Mesh2dHandle>, Without<Handle<Mesh>>);
+
+type PresentComponents = (
+    Entity,
+    &'static Handle<Svg>,
+    Has<Transform>,
+    Has<GlobalTransform>,
+    Has<Visibility>,
+    Has<InheritedVisibility>,
+    Has<ViewVisibility>,
+    Has<Origin>,
+    Has<SvgStatus>,
+);
+
+/// Bevy system which queries for all entities with a [`Handle<Svg>`] but without a mesh
+/// and adds the missing components of a [`Svg2dBundle`](crate::render::Svg2dBundle) or
+/// [`Svg3dBundle`](crate::render::Svg3dBundle) to them.
+///
+/// If both the `2d` and `3d` features are enabled, the 3D components are only added to
+/// entities with a [`Svg3d`](crate::render::Svg3d) marker.
+pub(crate) fn add_svg_components(
+    mut commands: Commands,
+    svgs: Res<Assets<Svg>>,
+    query: Query<PresentComponents, (With<Handle<Svg>>, WithoutMesh)>,
+    #[cfg(all(feature = "2d", feature = "3d"))] svg_3d_markers: Query<(), With<Svg3d>>,
+) {
+    for (
+        entity,
+        handle,
+        has_transform,
+        has_global_transform,
+        has_visibility,
+        has_inherited_visibility,
+        has_view_visibility,
+        has_origin,
+        has_status,
+    ) in &query
+    {
+        debug!(
+            "Entity `{:?}` has a `Handle<Svg>` without mesh. Adding missing components.",
+            entity
+        );
+        let mesh = svgs
+            .get(handle)
+            .map(|svg| svg.mesh.clone())
+            .unwrap_or_default();
+        let mut entity_commands = commands.entity(entity);
+
+        #[cfg(all(feature = "2d", feature = "3d"))]
+        if svg_3d_markers.contains(entity) {
+            entity_commands.insert(mesh);
+        } else {
+            entity_commands.insert(Mesh2dHandle(mesh));
+        }
+        #[cfg(feature = "2d")]
+        #[cfg(not(feature = "3d"))]
+        entity_commands.insert(Mesh2dHandle(mesh));
+        #[cfg(not(feature = "2d"))]
+        #[cfg(feature = "3d")]
+        entity_commands.insert(mesh);
+
+        if !has_transform {
+            entity_commands.insert(Transform::default());
+        }
+        if !has_global_transform {
+            entity_commands.insert(GlobalTransform::default());
+        }
+        if !has_visibility {
+            entity_commands.insert(Visibility::default());
+        }
+        if !has_inherited_visibility {
+            entity_commands.insert(InheritedVisibility::default());
+        }
+        if !has_view_visibility {
+            entity_commands.insert(ViewVisibility::default());
+        }
+        if !has_origin {
+            entity_commands.insert(Origin::default());
+        }
+        if !has_status {
+            entity_commands.insert(SvgStatus::default());
+        }
+    }
+}
+
 #[cfg(feature = "2d")]
 #[cfg(not(feature = "3d"))]
 type SvgMeshComponents = (
@@ -80,20 +281,38 @@ type SvgMeshComponents = (
     Option<&'static mut Handle<Mesh>>,
 );
 
+type ChangedSvgHandle = Or<(Changed<Handle<Svg>>, Added<Handle<Svg>>)>;
+
+/// The entities with an [`Svg`] and what happens to them when it is removed.
+#[derive(SystemParam)]
+pub(crate) struct SvgMeshQuery<'w, 's> {
+    query: Query<'w, 's, SvgMeshComponents>,
+    changed_handles: Query<'w, 's, Entity, ChangedSvgHandle>,
+    removal_policies: Query<'w, 's, &'static SvgRemovalPolicy>,
+    default_removal_policy: Res<'w, SvgRemovalPolicy>,
+}
+
 /// Bevy system which queries for all [`Svg`] bundles and adds the correct [`Mesh`] to them.
-fn svg_mesh_linker(
+///
+/// A modified [`Svg`] only counts as reloaded if its mesh was replaced or modified, so
+/// changes which keep the geometry, like path styles, don't respawn markers and layers.
+pub(crate) fn svg_mesh_linker(
     mut commands: Commands,
     mut svg_events: EventReader<AssetEvent<Svg>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
     mut meshes: ResMut<Assets<Mesh>>,
     svgs: Res<Assets<Svg>>,
-    mut query: Query<SvgMeshComponents>,
-    changed_handles: Query<Entity, Or<(Changed<Handle<Svg>>, Added<Handle<Svg>>)>>,
+    mut entities: SvgMeshQuery,
+    mut ready_events: EventWriter<SvgEvent>,
 ) {
+    let changed_meshes = changed_meshes(&mut mesh_events);
+    let query = &mut entities.query;
+    let mut ready = HashSet::new();
     for event in svg_events.read() {
         match event {
             AssetEvent::Added { .. } => (),
             AssetEvent::LoadedWithDependencies { id } => {
-                for (.., _mesh_2d, _mesh_3d) in query
+                for (entity, handle, _mesh_2d, _mesh_3d) in query
                     .iter_mut()
                     .filter(|(_, handle, ..)| handle.id() == *id)
                 {
@@ -106,48 +325,91 @@ fn svg_mesh_linker(
                     _mesh_2d.map(|mut mesh| mesh.0 = svg.mesh.clone());
                     #[cfg(feature = "3d")]
                     _mesh_3d.map(|mut mesh| *mesh = svg.mesh.clone());
+                    commands.entity(entity).insert(SvgStatus::Ready);
+                    ready_events.send(SvgEvent::Ready {
+                        entity,
+                        handle: handle.clone(),
+                    });
+                    ready.insert(entity);
                 }
             }
             AssetEvent::Modified { id } => {
-                for (.., _mesh_2d, _mesh_3d) in query
+                for mut linked in query
                     .iter_mut()
                     .filter(|(_, handle, ..)| handle.id() == *id)
                 {
                     let svg = svgs.get(*id).unwrap();
+                    let mut old_meshes = Vec::new();
+                    #[cfg(feature = "2d")]
+                    old_meshes.extend(
+                        linked
+                            .2
+                            .as_mut()
+                            .and_then(|mesh| mesh.replace_if_neq(Mesh2dHandle(svg.mesh.clone())))
+                            .map(|mesh| mesh.0),
+                    );
+                    #[cfg(feature = "3d")]
+                    old_meshes.extend(
+                        linked
+                            .3
+                            .as_mut()
+                            .and_then(|mesh| mesh.replace_if_neq(svg.mesh.clone())),
+                    );
+                    if old_meshes.is_empty() && !changed_meshes.contains(&svg.mesh.id()) {
+                        continue;
+                    }
+                    for old_mesh in old_meshes {
+                        meshes.remove(old_mesh);
+                    }
+                    let (entity, handle) = (linked.0, linked.1);
                     debug!(
-                        "Svg `{}` modified. Changing mesh component of entity.",
+                        "Svg `{}` modified. Changed mesh component of entity.",
                         svg.name
                     );
-                    #[cfg(feature = "2d")]
-                    _mesh_2d.filter(|mesh| mesh.0 != svg.mesh).map(|mut mesh| {
-                        let old_mesh = mesh.0.clone();
-                        mesh.0 = svg.mesh.clone();
-                        meshes.remove(old_mesh);
+                    commands.entity(entity).insert(SvgStatus::Ready);
+                    ready_events.send(SvgEvent::Reloaded {
+                        entity,
+                        handle: handle.clone(),
                     });
-                    #[cfg(feature = "3d")]
-                    _mesh_3d
-                        .filter(|mesh| mesh.deref() != &svg.mesh)
-                        .map(|mut mesh| {
-                            let old_mesh = mesh.clone();
-                            *mesh = svg.mesh.clone();
-                            meshes.remove(old_mesh);
-                        });
                 }
             }
             AssetEvent::Removed { id } => {
                 for (entity, ..) in query.iter_mut().filter(|(_, svg, ..)| svg.id() == *id) {
-                    commands.entity(entity).despawn_recursive();
+                    let policy = entities
+                        .removal_policies
+                        .get(entity)
+                        .copied()
+                        .unwrap_or(*entities.default_removal_policy);
+                    apply_removal_policy(&mut commands, entity, policy);
                 }
             }
         }
     }
 
-    // Ensure all correct meshes are set for entities which have had modified handles
-    for entity in changed_handles.iter() {
-        let Ok((.., handle, _mesh_2d, _mesh_3d)) = query.get_mut(entity) else {
+    link_changed_handles(
+        &mut commands,
+        &svgs,
+        &mut entities,
+        &ready,
+        &mut ready_events,
+    );
+}
+
+/// Ensures all correct meshes are set for entities which have had modified handles, and
+/// sends [`SvgEvent::Ready`] for those which are not `ready` yet.
+fn link_changed_handles(
+    commands: &mut Commands,
+    svgs: &Assets<Svg>,
+    entities: &mut SvgMeshQuery,
+    ready: &HashSet<Entity>,
+    ready_events: &mut EventWriter<SvgEvent>,
+) {
+    for entity in entities.changed_handles.iter() {
+        let Ok((.., handle, _mesh_2d, _mesh_3d)) = entities.query.get_mut(entity) else {
             continue;
         };
         let Some(svg) = svgs.get(handle) else {
+            commands.entity(entity).insert(SvgStatus::Loading);
             continue;
         };
         debug!(
@@ -158,5 +420,189 @@ fn svg_mesh_linker(
         _mesh_2d.map(|mut mesh| mesh.0 = svg.mesh.clone());
         #[cfg(feature = "3d")]
         _mesh_3d.map(|mut mesh| *mesh = svg.mesh.clone());
+        if !ready.contains(&entity) {
+            commands.entity(entity).insert(SvgStatus::Ready);
+            ready_events.send(SvgEvent::Ready {
+                entity,
+                handle: handle.clone(),
+            });
+        }
+    }
+}
+
+/// Applies the removal `policy` to an `entity` whose [`Svg`] was removed.
+fn apply_removal_policy(commands: &mut Commands, entity: Entity, policy: SvgRemovalPolicy) {
+    debug!(
+        "Svg of entity `{:?}` removed. Applying removal policy `{:?}`.",
+        entity, policy
+    );
+    match policy {
+        SvgRemovalPolicy::Despawn => commands.entity(entity).despawn_recursive(),
+        SvgRemovalPolicy::Hide => {
+            commands.entity(entity).insert(Visibility::Hidden);
+        }
+        SvgRemovalPolicy::KeepMesh => (),
+        SvgRemovalPolicy::RemoveSvg => {
+            commands
+                .entity(entity)
+                .remove::<(Handle<Svg>, Origin, OriginState, SvgPath, SvgStatus)>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::Update, ecs::entity::Entity};
+
+    use super::*;
+
+    fn app(policy: SvgRemovalPolicy) -> App {
+        let mut app = App::new();
+        app.add_event::<AssetEvent<Svg>>()
+            .add_event::<AssetEvent<Mesh>>()
+            .add_event::<SvgEvent>()
+            .init_resource::<Assets<Svg>>()
+            .init_resource::<Assets<Mesh>>()
+            .insert_resource(policy)
+            .add_systems(Update, svg_mesh_linker);
+        app
+    }
+
+    /// Spawns an entity with a loaded [`Svg`], removes the [`Svg`] and returns the entity.
+    fn remove_svg(app: &mut App, policy: Option<SvgRemovalPolicy>) -> Entity {
+        let svg = app.world.resource_mut::<Assets<Svg>>().add(Svg::default());
+        let mut entity = app.world.spawn((
+            svg.clone(),
+            SvgPath::new("map.svg"),
+            Origin::default(),
+            OriginState::default(),
+            SvgStatus::Ready,
+            Visibility::Inherited,
+            Handle::<Mesh>::default(),
+        ));
+        if let Some(policy) = policy {
+            entity.insert(policy);
+        }
+        let entity = entity.id();
+        app.update();
+
+        app.world.resource_mut::<Assets<Svg>>().remove(&svg);
+        app.world.send_event(AssetEvent::Removed { id: svg.id() });
+        app.update();
+        entity
+    }
+
+    #[test]
+    fn despawn() {
+        let mut app = app(SvgRemovalPolicy::Despawn);
+        let entity = remove_svg(&mut app, None);
+        assert!(app.world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn hide() {
+        let mut app = app(SvgRemovalPolicy::Hide);
+        let entity = remove_svg(&mut app, None);
+        assert_eq!(
+            app.world.get::<Visibility>(entity),
+            Some(&Visibility::Hidden)
+        );
+    }
+
+    #[test]
+    fn keep_mesh() {
+        let mut app = app(SvgRemovalPolicy::KeepMesh);
+        let entity = remove_svg(&mut app, None);
+        let entity = app.world.entity(entity);
+        assert!(entity.contains::<Handle<Svg>>());
+        assert!(entity.contains::<Handle<Mesh>>());
+        assert_eq!(entity.get::<Visibility>(), Some(&Visibility::Inherited));
+    }
+
+    #[test]
+    fn remove_svg_components() {
+        let mut app = app(SvgRemovalPolicy::RemoveSvg);
+        let entity = remove_svg(&mut app, None);
+        let entity = app.world.entity(entity);
+        assert!(entity.contains::<Handle<Mesh>>());
+        assert!(!entity.contains::<Handle<Svg>>());
+        assert!(!entity.contains::<Origin>());
+        assert!(!entity.contains::<OriginState>());
+        assert!(!entity.contains::<SvgStatus>());
+        // The `Svg` would be loaded again from its path
+        assert!(!entity.contains::<SvgPath>());
+    }
+
+    #[test]
+    fn component_overrides_resource() {
+        let mut app = app(SvgRemovalPolicy::KeepMesh);
+        let entity = remove_svg(&mut app, Some(SvgRemovalPolicy::Despawn));
+        assert!(app.world.get_entity(entity).is_none());
+    }
+
+    /// Spawns an entity with only a [`Handle<Svg>`] and a [`Transform`], optionally with a
+    /// [`Svg3d`] marker, and runs [`add_svg_components`] once.
+    #[cfg(all(feature = "2d", feature = "3d"))]
+    fn add_components(svg_3d: bool) -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Assets<Svg>>()
+            .add_systems(Update, add_svg_components);
+        let svg = app.world.resource_mut::<Assets<Svg>>().add(Svg::default());
+        let mut entity = app.world.spawn((svg, Transform::from_xyz(1.0, 2.0, 3.0)));
+        if svg_3d {
+            entity.insert(Svg3d);
+        }
+        let entity = entity.id();
+        app.update();
+        (app, entity)
+    }
+
+    /// Asserts that the missing components were added and the present ones kept, and that
+    /// another update doesn't add them again, which would reset the changed components.
+    #[cfg(all(feature = "2d", feature = "3d"))]
+    fn assert_components_added_once(app: &mut App, entity: Entity) {
+        let entity_ref = app.world.entity(entity);
+        assert_eq!(
+            entity_ref.get::<Transform>(),
+            Some(&Transform::from_xyz(1.0, 2.0, 3.0))
+        );
+        assert!(entity_ref.contains::<GlobalTransform>());
+        assert!(entity_ref.contains::<Visibility>());
+        assert!(entity_ref.contains::<InheritedVisibility>());
+        assert!(entity_ref.contains::<ViewVisibility>());
+        assert_eq!(entity_ref.get::<Origin>(), Some(&Origin::default()));
+        assert_eq!(entity_ref.get::<SvgStatus>(), Some(&SvgStatus::default()));
+
+        let mut entity_mut = app.world.entity_mut(entity);
+        entity_mut.insert((Origin::Center, SvgStatus::Ready, Visibility::Hidden));
+        app.update();
+        let entity_ref = app.world.entity(entity);
+        assert_eq!(entity_ref.get::<Origin>(), Some(&Origin::Center));
+        assert_eq!(entity_ref.get::<SvgStatus>(), Some(&SvgStatus::Ready));
+        assert_eq!(entity_ref.get::<Visibility>(), Some(&Visibility::Hidden));
+    }
+
+    #[test]
+    #[cfg(all(feature = "2d", feature = "3d"))]
+    fn adds_2d_components() {
+        let (mut app, entity) = add_components(false);
+        assert!(app.world.entity(entity).contains::<Mesh2dHandle>());
+        assert!(!app.world.entity(entity).contains::<Handle<Mesh>>());
+        let mesh = Mesh2dHandle(Handle::weak_from_u128(1));
+        app.world.entity_mut(entity).insert(mesh.clone());
+        assert_components_added_once(&mut app, entity);
+        assert_eq!(app.world.get::<Mesh2dHandle>(entity), Some(&mesh));
+    }
+
+    #[test]
+    #[cfg(all(feature = "2d", feature = "3d"))]
+    fn adds_3d_components_with_marker() {
+        let (mut app, entity) = add_components(true);
+        assert!(app.world.entity(entity).contains::<Handle<Mesh>>());
+        assert!(!app.world.entity(entity).contains::<Mesh2dHandle>());
+        let mesh = Handle::<Mesh>::weak_from_u128(1);
+        app.world.entity_mut(entity).insert(mesh.clone());
+        assert_components_added_once(&mut app, entity);
+        assert_eq!(app.world.get::<Handle<Mesh>>(entity), Some(&mesh));
     }
 }
@@ -0,0 +1,30 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        reflect::{ReflectComponent, ReflectResource},
+        system::Resource,
+    },
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+#[derive(Clone, Component, Copy, Debug, Default, Eq, PartialEq, Reflect, Resource)]
+#[reflect(Component, Resource, Default)]
+/// What happens to an entity when the [`Svg`](crate::svg::Svg) it uses is removed from
+/// [`Assets<Svg>`](bevy::asset::Assets).
+///
+/// Inserted as a resource, this is the policy for all SVG entities. Inserted as a component,
+/// it overrides the resource for that one entity.
+pub enum SvgRemovalPolicy {
+    /// Despawn the entity and all of its children.
+    Despawn,
+    /// Set the [`Visibility`](bevy::render::view::Visibility) of the entity to hidden.
+    Hide,
+    #[default]
+    /// Keep the last mesh of the SVG on the entity, this is the default.
+    KeepMesh,
+    /// Remove only the SVG related components, i.e. the [`Handle<Svg>`](crate::svg::Svg),
+    /// [`Origin`](crate::origin::Origin) and its internal state, the
+    /// [`SvgPath`](crate::scene::SvgPath) and the [`SvgStatus`](crate::status::SvgStatus),
+    /// from the entity.
+    RemoveSvg,
+}
@@ -0,0 +1,239 @@
+//! The bind group of the [`Svg`] material, shared by the 2D and 3D pipelines.
+
+// The `ShaderType` derive generates `check` functions for every field, which are never called
+#![allow(dead_code)]
+
+use bevy::{
+    log::error,
+    math::Vec4,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{
+            encase, AsBindGroup, AsBindGroupError, BindGroupLayout, BindGroupLayoutEntry,
+            BindingType, BufferBindingType, BufferInitDescriptor, BufferUsages,
+            OwnedBindingResource, ShaderStages, ShaderType, UnpreparedBindGroup,
+        },
+        renderer::RenderDevice,
+        texture::{FallbackImage, Image},
+    },
+};
+
+use crate::svg::{
+    PaintOrder, Svg, SvgAlphaMode, SvgCullMode, SvgLighting, SvgPathStyle, MAX_PATH_STYLES,
+};
+
+/// The settings of an [`Svg`] which specialize its render pipeline.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SvgMaterialKey {
+    pub(crate) depth_write: bool,
+    pub(crate) cull_mode: SvgCullMode,
+    pub(crate) alpha_add: bool,
+    pub(crate) depth_bias: i32,
+    pub(crate) lighting: SvgLighting,
+    /// Whether the shaders read the [`SvgPathStylesUniform`], see `SVG_PATH_STYLES`.
+    pub(crate) path_styles: bool,
+}
+
+impl From<&Svg> for SvgMaterialKey {
+    fn from(svg: &Svg) -> Self {
+        Self {
+            depth_write: svg.render_3d.paint_order != PaintOrder::NoDepthWrite,
+            cull_mode: svg.render_3d.cull_mode,
+            alpha_add: svg.render_3d.alpha_mode == SvgAlphaMode::Add,
+            depth_bias: svg.render_3d.depth_bias as i32,
+            lighting: svg.render_3d.lighting,
+            path_styles: !svg.path_styles.is_empty(),
+        }
+    }
+}
+
+/// The uniform of the [`Svg`] material.
+#[derive(Clone, ShaderType)]
+pub struct SvgMaterialUniform {
+    /// The threshold of [`SvgAlphaMode::Mask`], or of the shadows of the blended modes.
+    pub alpha_cutoff: f32,
+    /// The [`Svg::stroke_width`].
+    pub stroke_width: f32,
+}
+
+impl From<&Svg> for SvgMaterialUniform {
+    fn from(svg: &Svg) -> Self {
+        Self {
+            alpha_cutoff: match svg.render_3d.alpha_mode {
+                SvgAlphaMode::Mask(cutoff) => cutoff,
+                SvgAlphaMode::Blend | SvgAlphaMode::Premultiplied | SvgAlphaMode::Add => 0.05,
+                _ => 0.5,
+            },
+            stroke_width: svg.stroke_width,
+        }
+    }
+}
+
+/// The [`SvgPathStyle`] of every path index, in its own uniform, which is only uploaded for
+/// SVGs with [`Svg::path_styles`].
+#[derive(Clone, ShaderType)]
+pub struct SvgPathStylesUniform {
+    /// The styles by path index.
+    pub styles: [SvgPathStyleUniform; MAX_PATH_STYLES],
+}
+
+/// A [`SvgPathStyle`] in the uniform of the [`Svg`] material.
+#[derive(Clone, Copy, ShaderType)]
+pub struct SvgPathStyleUniform {
+    /// The color which replaces the color of the paths, in linear RGBA.
+    pub color: Vec4,
+    /// Multiplied with the alpha of the paths.
+    pub opacity: f32,
+    /// Multiplies the width of the strokes.
+    pub stroke_width: f32,
+    /// The `SVG_PATH_STYLE_*` flags in `svg_material.wgsl`.
+    pub flags: u32,
+}
+
+// Keep in sync with the `SVG_PATH_STYLE_*` flags in svg_material.wgsl
+const SVG_PATH_STYLE_HIDDEN: u32 = 1;
+const SVG_PATH_STYLE_COLOR: u32 = 2;
+
+impl From<&SvgPathStyle> for SvgPathStyleUniform {
+    fn from(style: &SvgPathStyle) -> Self {
+        let mut flags = 0;
+        if !style.visible {
+            flags |= SVG_PATH_STYLE_HIDDEN;
+        }
+        if style.color.is_some() {
+            flags |= SVG_PATH_STYLE_COLOR;
+        }
+        Self {
+            color: style
+                .color
+                .map_or(Vec4::ZERO, |color| Vec4::from(color.as_linear_rgba_f32())),
+            opacity: style.opacity,
+            stroke_width: style.stroke_width,
+            flags,
+        }
+    }
+}
+
+impl From<&Svg> for SvgPathStylesUniform {
+    fn from(svg: &Svg) -> Self {
+        let mut styles = [SvgPathStyleUniform::from(&SvgPathStyle::default()); MAX_PATH_STYLES];
+        for (&index, style) in &svg.path_styles {
+            if let Some(path_style) = styles.get_mut(index as usize) {
+                *path_style = style.into();
+            }
+        }
+        Self { styles }
+    }
+}
+
+impl AsBindGroup for Svg {
+    type Data = SvgMaterialKey;
+
+    fn label() -> Option<&'static str> {
+        Some("svg_material")
+    }
+
+    fn unprepared_bind_group(
+        &self,
+        _layout: &BindGroupLayout,
+        render_device: &RenderDevice,
+        _images: &RenderAssets<Image>,
+        _fallback_image: &FallbackImage,
+    ) -> Result<UnpreparedBindGroup<SvgMaterialKey>, AsBindGroupError> {
+        let material = uniform_buffer(render_device, &SvgMaterialUniform::from(self))?;
+        // Without path styles the shaders don't read the binding, so it only gets one style
+        let path_styles = if self.path_styles.is_empty() {
+            uniform_buffer(
+                render_device,
+                &SvgPathStyleUniform::from(&SvgPathStyle::default()),
+            )?
+        } else {
+            uniform_buffer(render_device, &SvgPathStylesUniform::from(self))?
+        };
+        Ok(UnpreparedBindGroup {
+            bindings: vec![(0, material), (1, path_styles)],
+            data: self.into(),
+        })
+    }
+
+    fn bind_group_layout_entries(_render_device: &RenderDevice) -> Vec<BindGroupLayoutEntry> {
+        vec![
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(SvgMaterialUniform::min_size()),
+                },
+                count: None,
+            },
+            // The size of the path styles depends on whether the SVG has any
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ]
+    }
+}
+
+/// Creates a uniform buffer with the contents of `value`.
+fn uniform_buffer<T: ShaderType + encase::internal::WriteInto>(
+    render_device: &RenderDevice,
+    value: &T,
+) -> Result<OwnedBindingResource, AsBindGroupError> {
+    let mut buffer = encase::UniformBuffer::new(Vec::new());
+    // The buffer grows with the value, so writing only fails if encase can't lay it out
+    buffer.write(value).map_err(|err| {
+        error!("Failed to write the uniforms of the SVG material: {err}");
+        AsBindGroupError::RetryNextUpdate
+    })?;
+    Ok(OwnedBindingResource::Buffer(
+        render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("svg_material_uniform_buffer"),
+            contents: buffer.as_ref(),
+            usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn path_styles_are_only_read_if_present() {
+        let mut svg = Svg::default();
+        assert!(!SvgMaterialKey::from(&svg).path_styles);
+        assert!(SvgMaterialUniform::min_size().get() <= 16);
+
+        let hidden = SvgPathStyle {
+            visible: false,
+            color: Some(Color::RED),
+            ..Default::default()
+        };
+        svg.path_styles.insert(3, hidden);
+        svg.path_styles.insert(MAX_PATH_STYLES as u32, hidden);
+        assert!(SvgMaterialKey::from(&svg).path_styles);
+
+        let uniform = SvgPathStylesUniform::from(&svg);
+        assert_eq!(
+            uniform.styles[3].flags,
+            SVG_PATH_STYLE_HIDDEN | SVG_PATH_STYLE_COLOR
+        );
+        assert_eq!(uniform.styles[3].color, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert!(uniform
+            .styles
+            .iter()
+            .enumerate()
+            .all(|(index, style)| index == 3 || style.flags == 0));
+    }
+}
@@ -1,6 +1,11 @@
+#[cfg(any(feature = "2d", feature = "3d"))]
+use bevy::{asset::Handle, render::render_resource::Shader};
+
+pub(crate) mod material;
+#[cfg(any(feature = "2d", feature = "3d"))]
 mod plugin;
 pub(crate) mod tessellation;
-mod vertex_buffer;
+pub(crate) mod vertex_buffer;
 
 #[cfg(feature = "2d")]
 mod svg2d;
@@ -8,8 +13,15 @@ mod svg2d;
 mod svg3d;
 
 #[cfg(feature = "2d")]
-pub use svg2d::Svg2dBundle;
+pub use svg2d::{Svg2d, Svg2dBundle};
 #[cfg(feature = "3d")]
-pub use svg3d::Svg3dBundle;
+pub use svg3d::{Svg3d, Svg3dBundle, SvgBillboard, SvgBillboardMode, SvgDecal, SvgDecalMesh};
 
+#[cfg(any(feature = "2d", feature = "3d"))]
 pub use plugin::SvgPlugin;
+pub use vertex_buffer::{SvgVertexConstructor, SvgVertexContext};
+
+/// Handle to the shader module with the material bindings, shared by the 2D and 3D shaders
+#[cfg(any(feature = "2d", feature = "3d"))]
+pub const SVG_MATERIAL_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(2_806_351_079_425_179_071);
@@ -1,5 +1,12 @@
-use crate::resources::{FillTessellator, StrokeTessellator};
-use bevy::app::{App, Plugin};
+use crate::{
+    render::SVG_MATERIAL_SHADER_HANDLE,
+    resources::{FillTessellator, StrokeTessellator},
+};
+use bevy::{
+    app::{App, Plugin},
+    asset::load_internal_asset,
+    render::render_resource::Shader,
+};
 
 #[cfg(feature = "2d")]
 use crate::render::svg2d;
@@ -11,6 +18,13 @@ pub struct SvgPlugin;
 
 impl Plugin for SvgPlugin {
     fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SVG_MATERIAL_SHADER_HANDLE,
+            "svg_material.wgsl",
+            Shader::from_wgsl
+        );
+
         let fill_tess = FillTessellator::default();
         let stroke_tess = StrokeTessellator::default();
         app.insert_resource(fill_tess).insert_resource(stroke_tess);
@@ -2,13 +2,22 @@
 
 use bevy::{
     asset::Handle,
-    ecs::bundle::Bundle,
+    ecs::{bundle::Bundle, component::Component, reflect::ReflectComponent},
+    reflect::{std_traits::ReflectDefault, Reflect},
     render::view::{InheritedVisibility, ViewVisibility, Visibility},
     sprite::Mesh2dHandle,
     transform::components::{GlobalTransform, Transform},
 };
 
-use crate::{origin::Origin, svg::Svg};
+use crate::{origin::Origin, status::SvgStatus, svg::Svg};
+
+/// Marker component for an SVG entity that should be rendered in 2D.
+///
+/// An entity with a [`Handle<Svg>`] but without a mesh handle gets all missing components
+/// of a [`Svg2dBundle`] added automatically. This marker selects the 2D variant.
+#[derive(Clone, Component, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct Svg2d;
 
 /// A Bevy [`Bundle`] representing an SVG entity.
 #[allow(missing_docs)]
@@ -18,6 +27,8 @@ pub struct Svg2dBundle {
     pub mesh_2d: Mesh2dHandle,
     /// [`Origin`] of the coordinate system and as such the origin for the Bevy position.
     pub origin: Origin,
+    /// [`SvgStatus`] of the [`Svg`], this is updated by the plugin.
+    pub status: SvgStatus,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
     pub visibility: Visibility,
@@ -29,9 +40,10 @@ impl Default for Svg2dBundle {
     /// Creates a default [`Svg2dBundle`].
     fn default() -> Self {
         Self {
-            svg: Default::default(),
-            mesh_2d: Default::default(),
-            origin: Default::default(),
+            svg: Handle::default(),
+            mesh_2d: Mesh2dHandle::default(),
+            origin: Origin::default(),
+            status: SvgStatus::default(),
             transform: Transform::default(),
             global_transform: GlobalTransform::default(),
             visibility: Visibility::default(),
@@ -5,6 +5,9 @@ mod plugin;
 
 /// Handle to the custom shader with a unique random ID
 pub const SVG_2D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8_514_826_620_251_853_414);
+/// Handle to the vertex shader of meshes with the vertex attributes of this crate
+pub const SVG_2D_VERTEX_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(2_806_351_079_425_179_087);
 
-pub use bundle::Svg2dBundle;
+pub use bundle::{Svg2d, Svg2dBundle};
 pub use plugin::RenderPlugin;
@@ -1,11 +1,25 @@
 use bevy::{
     app::{App, Plugin},
     asset::{load_internal_asset, AssetApp},
-    render::render_resource::{Shader, ShaderRef},
-    sprite::{Material2d, Material2dPlugin},
+    ecs::schedule::IntoSystemConfigs,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_resource::{
+            RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError,
+        },
+        ExtractSchedule, RenderApp,
+    },
+    sprite::{extract_mesh2d, Material2d, Material2dKey, Material2dPlugin},
 };
 
-use crate::{render::svg2d::SVG_2D_SHADER_HANDLE, svg::Svg};
+use crate::{
+    render::svg2d::{Svg2d, SVG_2D_SHADER_HANDLE, SVG_2D_VERTEX_SHADER_HANDLE},
+    stroke,
+    svg::{
+        Svg, ATTRIBUTE_COLOR_PACKED, ATTRIBUTE_PATH_INDEX, ATTRIBUTE_POSITION_2D,
+        ATTRIBUTE_STROKE_NORMAL,
+    },
+};
 
 /// Plugin that renders [`Svg`](crate::svg::Svg)s in 2D
 pub struct RenderPlugin;
@@ -13,9 +27,23 @@ pub struct RenderPlugin;
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
         load_internal_asset!(app, SVG_2D_SHADER_HANDLE, "svg_2d.wgsl", Shader::from_wgsl);
+        load_internal_asset!(
+            app,
+            SVG_2D_VERTEX_SHADER_HANDLE,
+            "svg_2d_vertex.wgsl",
+            Shader::from_wgsl
+        );
 
         app.add_plugins(Material2dPlugin::<Svg>::default())
-            .register_asset_reflect::<Svg>();
+            .register_asset_reflect::<Svg>()
+            .register_type::<Svg2d>();
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                ExtractSchedule,
+                stroke::extract_stroke_widths_2d.after(extract_mesh2d),
+            );
+        }
     }
 }
 
@@ -23,4 +51,47 @@ impl Material2d for Svg {
     fn fragment_shader() -> ShaderRef {
         SVG_2D_SHADER_HANDLE.into()
     }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if layout.contains(ATTRIBUTE_PATH_INDEX) {
+            let path_index = layout.get_layout(&[ATTRIBUTE_PATH_INDEX.at_shader_location(9)])?;
+            descriptor.vertex.buffers[0]
+                .attributes
+                .extend(path_index.attributes);
+            descriptor.vertex.shader = SVG_2D_VERTEX_SHADER_HANDLE;
+            descriptor.vertex.shader_defs.push("SVG_PATH_INDEX".into());
+            if key.bind_group_data.path_styles {
+                descriptor.vertex.shader_defs.push("SVG_PATH_STYLES".into());
+            }
+        }
+        if layout.contains(ATTRIBUTE_STROKE_NORMAL) {
+            let stroke = layout.get_layout(&[ATTRIBUTE_STROKE_NORMAL.at_shader_location(10)])?;
+            descriptor.vertex.buffers[0]
+                .attributes
+                .extend(stroke.attributes);
+            descriptor.vertex.shader = SVG_2D_VERTEX_SHADER_HANDLE;
+            descriptor.vertex.shader_defs.push("SVG_STROKE".into());
+        }
+        // Bevy only knows the standard position and color attributes
+        if layout.contains(ATTRIBUTE_POSITION_2D) {
+            let compact = layout.get_layout(&[
+                ATTRIBUTE_POSITION_2D.at_shader_location(0),
+                ATTRIBUTE_COLOR_PACKED.at_shader_location(4),
+            ])?;
+            descriptor.vertex.buffers[0]
+                .attributes
+                .extend(compact.attributes);
+            descriptor.vertex.shader = SVG_2D_VERTEX_SHADER_HANDLE;
+            descriptor.vertex.shader_defs.push("SVG_COMPACT".into());
+            descriptor.vertex.shader_defs.push("VERTEX_COLORS".into());
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("VERTEX_COLORS".into());
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,129 @@
+#import bevy_sprite::{
+    mesh2d_bindings::mesh,
+    mesh2d_functions as mesh_functions,
+    mesh2d_vertex_output::VertexOutput,
+    mesh2d_view_bindings::view,
+}
+#import bevy_render::instance_index::get_instance_index
+#import bevy_svg::svg_material::{
+    entity_stroke, material, path_color, path_hidden, path_stroke_width, unpack_color,
+}
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+#ifdef SVG_COMPACT
+    @location(0) position: vec2<f32>,
+#else
+    @location(0) position: vec3<f32>,
+#endif
+#ifdef VERTEX_UVS
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(4) color: vec4<f32>,
+#endif
+#ifdef SVG_PATH_INDEX
+    @location(9) path_index: u32,
+#endif
+#ifdef SVG_STROKE
+    // The normal, the half width and the side of the stroke. A negative half width is given
+    // in pixels on screen and the vertex lies on the centerline.
+    @location(10) stroke: vec4<f32>,
+#endif
+};
+
+#ifdef SVG_STROKE
+// Multiplies the width of the stroke of the vertex
+fn stroke_width(vertex: Vertex) -> f32 {
+#ifdef SVG_PATH_INDEX
+    return material.stroke_width * path_stroke_width(vertex.path_index);
+#else
+    return material.stroke_width;
+#endif
+}
+
+// The stroke normal of the vertex with the `SvgStrokeWidth` of the entity
+fn stroke_normal(vertex: Vertex) -> vec4<f32> {
+    let flags = mesh[get_instance_index(vertex.instance_index)].flags;
+    return entity_stroke(vertex.stroke, flags);
+}
+
+// Moves a vertex of a stroke with a width in pixels from the centerline along the normal as
+// it is seen on screen
+fn screen_stroke(
+    model: mat4x4<f32>,
+    vertex: Vertex,
+    position: vec3<f32>,
+    clip: vec4<f32>,
+) -> vec4<f32> {
+    let stroke = stroke_normal(vertex);
+    if stroke.z >= 0.0 {
+        return clip;
+    }
+    let normal_world = mesh_functions::mesh2d_position_local_to_world(
+        model,
+        vec4<f32>(position + vec3<f32>(stroke.xy, 0.0), 1.0)
+    );
+    let normal_clip = mesh_functions::mesh2d_position_world_to_clip(normal_world);
+    let half_viewport = view.viewport.zw * 0.5;
+    let normal = (normal_clip.xy / normal_clip.w - clip.xy / clip.w) * half_viewport;
+    let normal_length = length(normal);
+    if normal_length < 1e-6 {
+        return clip;
+    }
+    // The length of the normal is kept for the miters of the joins
+    let half_width = -stroke.z * length(stroke.xy) * stroke_width(vertex);
+    let offset = normal / normal_length * half_width / half_viewport;
+    return vec4<f32>(clip.xy + offset * clip.w, clip.zw);
+}
+#endif
+
+// Only used for compact meshes and meshes with a path index or stroke normals, the others are
+// drawn by the vertex shader of Bevy
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+#ifdef VERTEX_UVS
+    out.uv = vertex.uv;
+#endif
+
+#ifdef SVG_COMPACT
+    var position = vec3<f32>(vertex.position, 0.0);
+#else
+    var position = vertex.position;
+#endif
+#ifdef SVG_STROKE
+    // The vertex is moved along the normal, away from or towards the centerline, and onto it
+    // if the stroke of the entity has a width on screen
+    let half_width = max(stroke_normal(vertex).z, 0.0) * stroke_width(vertex);
+    let offset = vertex.stroke.xy * (half_width - max(vertex.stroke.z, 0.0));
+    position += vec3<f32>(offset, 0.0);
+#endif
+    let model = mesh_functions::get_model_matrix(vertex.instance_index);
+    out.world_position = mesh_functions::mesh2d_position_local_to_world(
+        model,
+        vec4<f32>(position, 1.0)
+    );
+    out.position = mesh_functions::mesh2d_position_world_to_clip(out.world_position);
+#ifdef SVG_STROKE
+    out.position = screen_stroke(model, vertex, position, out.position);
+#endif
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#ifdef SVG_COMPACT
+    out.color = unpack_color(out.color);
+#endif
+#ifdef SVG_PATH_INDEX
+    out.color = path_color(out.color, vertex.path_index);
+#endif
+#endif
+
+#ifdef SVG_PATH_INDEX
+    // All vertices of a hidden path are collapsed, so its triangles cover no pixels
+    if path_hidden(vertex.path_index) {
+        out.position = vec4<f32>(0.0);
+    }
+#endif
+    return out;
+}
@@ -0,0 +1,187 @@
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Added, Has, Without},
+        reflect::ReflectComponent,
+        removal_detection::RemovedComponents,
+        system::{Commands, Query, Res, ResMut},
+    },
+    math::{Affine3, Mat3, Vec2, Vec3},
+    pbr::RenderMeshInstances,
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::{view::NoFrustumCulling, Extract},
+};
+
+use crate::{origin::Origin, svg::Svg};
+
+/// Bit of the mesh flags of billboards, which is not used by Bevy. Keep in sync with
+/// `SVG_MESH_FLAGS_BILLBOARD_BIT` in `svg_3d_vertex.wgsl`.
+const MESH_FLAGS_BILLBOARD: u32 = 1 << 24;
+
+#[derive(Clone, Component, Copy, Debug, Default, Eq, Hash, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// Turns a 3D SVG entity towards the camera, e.g. for markers or nameplates above units.
+///
+/// The SVG is turned around its [`Origin`] in the vertex shader, so no transform is updated
+/// on the CPU. The rotation of the entity is ignored, its translation and scale are kept.
+/// In shadow maps, billboards face the light.
+///
+/// The entity keeps the mesh of its [`Svg`], the billboard settings are passed to the shader
+/// with the transform of the entity. It gets a [`NoFrustumCulling`], because the bounds of
+/// the mesh don't contain the turned SVG.
+pub struct SvgBillboard {
+    /// How the SVG is turned towards the camera.
+    pub mode: SvgBillboardMode,
+    /// Whether the SVG has the same size on screen at any distance. One unit of the SVG,
+    /// multiplied with the scale of the entity, then covers one physical pixel.
+    pub constant_size: bool,
+}
+
+/// How a [`SvgBillboard`] is turned towards the camera.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Reflect)]
+#[reflect(Default, Debug)]
+pub enum SvgBillboardMode {
+    #[default]
+    /// The SVG faces the position of the camera, this is the default.
+    Full,
+    /// The SVG stays upright along the y-axis and only turns around it to face the camera.
+    Cylindrical,
+    /// The SVG is parallel to the screen, which keeps the billboards at the edges of a
+    /// perspective view from being distorted differently than those in the center.
+    ScreenAligned,
+}
+
+/// Marks a [`NoFrustumCulling`] which was added because the entity is a billboard.
+#[derive(Component)]
+pub(crate) struct SvgBillboardNoCulling;
+
+/// Adds a [`NoFrustumCulling`] to new billboards and removes it again from entities whose
+/// [`SvgBillboard`] was removed. A [`NoFrustumCulling`] which was added by the user is never
+/// removed.
+pub(crate) fn update_billboard_culling(
+    mut commands: Commands,
+    added: Query<Entity, (Added<SvgBillboard>, Without<NoFrustumCulling>)>,
+    mut removed: RemovedComponents<SvgBillboard>,
+    others: Query<Has<SvgBillboardNoCulling>, Without<SvgBillboard>>,
+) {
+    for entity in &added {
+        commands
+            .entity(entity)
+            .insert((NoFrustumCulling, SvgBillboardNoCulling));
+    }
+    for entity in removed.read() {
+        if others.get(entity) == Ok(true) {
+            commands
+                .entity(entity)
+                .remove::<(NoFrustumCulling, SvgBillboardNoCulling)>();
+        }
+    }
+}
+
+type BillboardComponents = (
+    Entity,
+    &'static SvgBillboard,
+    &'static Handle<Svg>,
+    Option<&'static Origin>,
+);
+
+/// Passes the settings and the pivot of every visible billboard to the vertex shader in
+/// place of the rotation of its transform, which billboards ignore.
+pub(crate) fn extract_billboards(
+    svgs: Extract<Res<Assets<Svg>>>,
+    billboards: Extract<Query<BillboardComponents>>,
+    mut mesh_instances: ResMut<RenderMeshInstances>,
+) {
+    for (entity, billboard, handle, origin) in &billboards {
+        let Some(mesh_instance) = mesh_instances.get_mut(&entity) else {
+            continue;
+        };
+        let size = svgs.get(handle).map_or(Vec2::ZERO, |svg| svg.size);
+        let pivot = -origin
+            .copied()
+            .unwrap_or_default()
+            .compute_translation(size);
+
+        let transforms = &mut mesh_instance.transforms;
+        transforms.transform = billboard.transform(&transforms.transform, pivot.truncate());
+        transforms.previous_transform =
+            billboard.transform(&transforms.previous_transform, pivot.truncate());
+        transforms.flags |= MESH_FLAGS_BILLBOARD;
+    }
+}
+
+impl SvgBillboard {
+    /// Replaces the 3x3 matrix of `transform` with the columns the vertex shader of
+    /// billboards expects: the scale, the mode and whether the size is constant on screen,
+    /// and the pivot in mesh coordinates.
+    fn transform(self, transform: &Affine3, pivot: Vec2) -> Affine3 {
+        let matrix = transform.matrix3;
+        let scale = Vec3::new(
+            matrix.x_axis.length(),
+            matrix.y_axis.length(),
+            matrix.z_axis.length(),
+        );
+        let mode = match self.mode {
+            SvgBillboardMode::Full => 0.0,
+            SvgBillboardMode::Cylindrical => 1.0,
+            SvgBillboardMode::ScreenAligned => 2.0,
+        };
+        let constant_size = if self.constant_size { 1.0 } else { 0.0 };
+        Affine3 {
+            matrix3: Mat3::from_cols(
+                scale,
+                Vec3::new(mode, constant_size, 0.0),
+                pivot.extend(0.0),
+            ),
+            translation: transform.translation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, math::Quat, transform::components::Transform};
+
+    use super::*;
+
+    #[test]
+    fn transform_keeps_scale_and_translation() {
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0)
+            .with_rotation(Quat::from_rotation_y(1.0))
+            .with_scale(Vec3::new(2.0, 3.0, 4.0));
+        let billboard = SvgBillboard {
+            mode: SvgBillboardMode::Cylindrical,
+            constant_size: true,
+        };
+        let affine =
+            billboard.transform(&(&transform.compute_affine()).into(), Vec2::new(-5.0, 6.0));
+        assert!(affine.matrix3.x_axis.abs_diff_eq(transform.scale, 1e-5));
+        assert_eq!(affine.matrix3.y_axis, Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(affine.matrix3.z_axis, Vec3::new(-5.0, 6.0, 0.0));
+        assert_eq!(affine.translation, transform.translation);
+    }
+
+    #[test]
+    fn culling_follows_billboard() {
+        let mut app = App::new();
+        app.add_systems(bevy::app::Update, update_billboard_culling);
+        let billboard = app.world.spawn(SvgBillboard::default()).id();
+        let culled = app
+            .world
+            .spawn((SvgBillboard::default(), NoFrustumCulling))
+            .id();
+        app.update();
+        assert!(app.world.get::<NoFrustumCulling>(billboard).is_some());
+
+        app.world.entity_mut(billboard).remove::<SvgBillboard>();
+        app.world.entity_mut(culled).remove::<SvgBillboard>();
+        app.update();
+        assert!(app.world.get::<NoFrustumCulling>(billboard).is_none());
+        assert!(
+            app.world.get::<NoFrustumCulling>(culled).is_some(),
+            "a NoFrustumCulling of the user is kept"
+        );
+    }
+}
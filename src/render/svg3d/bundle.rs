@@ -2,7 +2,8 @@
 
 use bevy::{
     asset::Handle,
-    ecs::bundle::Bundle,
+    ecs::{bundle::Bundle, component::Component, reflect::ReflectComponent},
+    reflect::{std_traits::ReflectDefault, Reflect},
     render::{
         mesh::Mesh,
         view::{InheritedVisibility, ViewVisibility, Visibility},
@@ -10,7 +11,15 @@ use bevy::{
     transform::components::{GlobalTransform, Transform},
 };
 
-use crate::{origin::Origin, svg::Svg};
+use crate::{origin::Origin, status::SvgStatus, svg::Svg};
+
+/// Marker component for an SVG entity that should be rendered in 3D.
+///
+/// An entity with a [`Handle<Svg>`] but without a mesh handle gets all missing components
+/// of a [`Svg3dBundle`] added automatically. This marker selects the 3D variant.
+#[derive(Clone, Component, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct Svg3d;
 
 /// A Bevy [`Bundle`] representing an SVG entity.
 #[allow(missing_docs)]
@@ -20,6 +29,8 @@ pub struct Svg3dBundle {
     pub mesh: Handle<Mesh>,
     /// [`Origin`] of the coordinate system and as such the origin for the Bevy position.
     pub origin: Origin,
+    /// [`SvgStatus`] of the [`Svg`], this is updated by the plugin.
+    pub status: SvgStatus,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
     pub visibility: Visibility,
@@ -31,9 +42,10 @@ impl Default for Svg3dBundle {
     /// Creates a default [`Svg3dBundle`].
     fn default() -> Self {
         Self {
-            svg: Default::default(),
-            mesh: Default::default(),
-            origin: Default::default(),
+            svg: Handle::default(),
+            mesh: Handle::default(),
+            origin: Origin::default(),
+            status: SvgStatus::default(),
             transform: Transform::default(),
             global_transform: GlobalTransform::default(),
             visibility: Visibility::default(),
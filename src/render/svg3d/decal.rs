@@ -0,0 +1,720 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::{
+    asset::{AssetEvent, Assets, Handle},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::{Changed, With, Without},
+        reflect::ReflectComponent,
+        removal_detection::RemovedComponents,
+        system::{Commands, Query, ResMut, SystemParam},
+    },
+    hierarchy::{BuildChildren, Children, DespawnRecursiveExt},
+    math::{Affine3A, Mat3A, Vec2, Vec3, Vec3A, Vec3Swizzles, Vec4},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::{
+        color::Color,
+        mesh::{Indices, Mesh, VertexAttributeValues},
+        prelude::SpatialBundle,
+        primitives::Aabb,
+        render_resource::PrimitiveTopology,
+    },
+    transform::components::GlobalTransform,
+};
+
+#[cfg(feature = "2d")]
+use super::Svg3d;
+use crate::svg::{PaintOrder, Svg, ViewBox, ATTRIBUTE_COLOR_PACKED, ATTRIBUTE_POSITION_2D};
+
+#[derive(Clone, Component, Debug, Reflect)]
+#[reflect(Component, Default)]
+/// Projects an [`Svg`] onto the meshes underneath, e.g. graffiti on a wall or road markings
+/// on uneven ground.
+///
+/// The decal is an oriented box centered on the entity, which projects the [`Svg`] along
+/// its negative z-axis. The [`Svg`] is stretched over the x and y extent of the box. The
+/// triangles of all meshes inside the box which face the projection are clipped to the box
+/// and to the triangles of the [`Svg`], and get the colors of the [`Svg`]. The result is
+/// shown by a child entity with a [`SvgDecalMesh`], which uses the [`Svg3dSettings`] of the
+/// [`Svg`], but doesn't write depth, so the paths stay in paint order.
+///
+/// The decal is baked when it is added or changed and when the [`Svg`] is loaded or
+/// modified. When the entity or the meshes underneath move, only the meshes which moved
+/// relative to the decal are projected again. Changes of the mesh assets underneath are not
+/// followed, mark the [`SvgDecal`] as changed to bake it again. Other SVG entities don't
+/// receive decals.
+///
+/// [`Svg3dSettings`]: crate::svg::Svg3dSettings
+pub struct SvgDecal {
+    /// The projected [`Svg`].
+    pub svg: Handle<Svg>,
+    /// Size of the box in the local units of the entity.
+    pub size: Vec3,
+    /// Distance along the z-axis, in the local units of the entity, by which the decal is
+    /// lifted off the surfaces to keep it from z-fighting with them.
+    pub offset: f32,
+}
+
+impl Default for SvgDecal {
+    fn default() -> Self {
+        Self {
+            svg: Handle::default(),
+            size: Vec3::ONE,
+            offset: 0.001,
+        }
+    }
+}
+
+#[derive(Clone, Component, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+/// The child entity of a [`SvgDecal`] which shows the baked decal.
+pub struct SvgDecalMesh;
+
+/// The projected decals of the meshes underneath a [`SvgDecal`], by their entity, and where
+/// the meshes were relative to the decal when they were projected.
+#[derive(Component, Default)]
+pub(crate) struct SvgDecalTargets(BTreeMap<Entity, (Affine3A, DecalBuffers)>);
+
+type DecalComponents = (
+    Entity,
+    &'static SvgDecal,
+    &'static GlobalTransform,
+    Option<&'static mut SvgDecalTargets>,
+    Option<&'static Children>,
+);
+type TargetComponents = (
+    Entity,
+    &'static Handle<Mesh>,
+    &'static GlobalTransform,
+    Option<&'static Aabb>,
+);
+type MovedTarget = (
+    Changed<GlobalTransform>,
+    With<Handle<Mesh>>,
+    Without<Handle<Svg>>,
+);
+
+/// The entities with a [`SvgDecal`] and their baked [`SvgDecalMesh`]es.
+#[derive(SystemParam)]
+pub(crate) struct SvgDecalQuery<'w, 's> {
+    decals: Query<'w, 's, DecalComponents>,
+    changed: Query<'w, 's, (), Changed<SvgDecal>>,
+    moved: Query<'w, 's, (), Changed<GlobalTransform>>,
+    decal_meshes: Query<'w, 's, (), With<SvgDecalMesh>>,
+}
+
+/// The meshes which can receive decals, and those which moved or were removed.
+#[derive(SystemParam)]
+pub(crate) struct DecalTargetQuery<'w, 's> {
+    targets: Query<'w, 's, TargetComponents, Without<Handle<Svg>>>,
+    moved: Query<'w, 's, Entity, MovedTarget>,
+    removed: RemovedComponents<'w, 's, Handle<Mesh>>,
+}
+
+/// Bakes every new or changed [`SvgDecal`] and every decal whose [`Svg`] was loaded or
+/// modified, and projects the meshes which moved relative to a decal again, replacing the
+/// previous [`SvgDecalMesh`] of the decal.
+pub(crate) fn bake_svg_decals(
+    mut commands: Commands,
+    mut svg_events: EventReader<AssetEvent<Svg>>,
+    mut svgs: ResMut<Assets<Svg>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut decals: SvgDecalQuery,
+    mut targets: DecalTargetQuery,
+) {
+    let loaded: HashSet<_> = svg_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let removed_targets: HashSet<_> = targets.removed.read().collect();
+
+    for (entity, decal, transform, baked, children) in &mut decals.decals {
+        let bake_all =
+            baked.is_none() || decals.changed.contains(entity) || loaded.contains(&decal.svg.id());
+        let decal_moved = decals.moved.contains(entity);
+        if !bake_all && !decal_moved && targets.moved.is_empty() && removed_targets.is_empty() {
+            continue;
+        }
+        let Some(svg) = svgs.get(&decal.svg) else {
+            continue;
+        };
+        let Some(svg_triangles) = meshes
+            .get(&svg.mesh)
+            .and_then(|mesh| SvgTriangle::from_mesh(mesh, svg.view_box, decal.size.truncate()))
+        else {
+            continue;
+        };
+
+        let mut baked = match baked {
+            Some(mut baked) if !bake_all => std::mem::take(&mut baked.0),
+            _ => BTreeMap::new(),
+        };
+        let count = baked.len();
+        baked.retain(|target, _| !removed_targets.contains(target));
+        let mut modified = bake_all || baked.len() != count;
+
+        let to_decal = transform.affine().inverse();
+        let updated: Vec<Entity> = if bake_all || decal_moved {
+            targets.targets.iter().map(|(target, ..)| target).collect()
+        } else {
+            targets.moved.iter().collect()
+        };
+        for target in updated {
+            let Ok((_, mesh, target_transform, aabb)) = targets.targets.get(target) else {
+                continue;
+            };
+            let to_decal = to_decal * target_transform.affine();
+            if baked
+                .get(&target)
+                .is_some_and(|(previous, _)| *previous == to_decal)
+            {
+                continue;
+            }
+            let buffers = meshes
+                .get(mesh)
+                .map(|mesh| project(&svg_triangles, mesh, &to_decal, aabb, decal))
+                .unwrap_or_default();
+            let previous = if buffers.indices.is_empty() {
+                baked.remove(&target)
+            } else {
+                modified = true;
+                baked.insert(target, (to_decal, buffers))
+            };
+            modified |= previous.is_some();
+        }
+
+        let mut buffers = DecalBuffers::default();
+        if modified {
+            for (_, target_buffers) in baked.values() {
+                buffers.extend(target_buffers);
+            }
+        }
+        commands.entity(entity).insert(SvgDecalTargets(baked));
+        if !modified {
+            continue;
+        }
+
+        for child in children.into_iter().flatten() {
+            if decals.decal_meshes.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        if buffers.indices.is_empty() {
+            continue;
+        }
+
+        let mut decal_svg = Svg {
+            groups: Vec::new(),
+            mesh: meshes.add(buffers.into_mesh()),
+            ..svg.with_paths(format!("{}#decal", svg.name), Vec::new())
+        };
+        decal_svg.render_3d.paint_order = PaintOrder::NoDepthWrite;
+        let mut child = commands.spawn((
+            Name::new(decal_svg.name.clone()),
+            SpatialBundle::default(),
+            svgs.add(decal_svg),
+            SvgDecalMesh,
+        ));
+        #[cfg(feature = "2d")]
+        child.insert(Svg3d);
+        child.set_parent(entity);
+    }
+}
+
+/// Projects the SVG triangles onto the triangles of `mesh` inside the box of `decal`, with
+/// `to_decal` from the space of the mesh to the space of the decal.
+fn project(
+    svg_triangles: &[SvgTriangle],
+    mesh: &Mesh,
+    to_decal: &Affine3A,
+    aabb: Option<&Aabb>,
+    decal: &SvgDecal,
+) -> DecalBuffers {
+    let half_size = decal.size * 0.5;
+    let mut buffers = DecalBuffers::default();
+    if aabb.is_some_and(|aabb| !overlaps_box(aabb, to_decal, half_size)) {
+        return buffers;
+    }
+    // A mirrored transform turns the front faces around
+    let facing = if to_decal.matrix3.determinant() < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    for [a, b, c] in triangles(mesh) {
+        let [a, b, c] = [a, b, c].map(|p| Vec3::from(to_decal.transform_point3a(p)));
+        // Only surfaces facing the projection receive the decal, in counter-clockwise order
+        let normal = (b - a).cross(c - a) * facing;
+        if normal.z <= f32::EPSILON {
+            continue;
+        }
+        let [a, b, c] = if facing < 0.0 { [a, c, b] } else { [a, b, c] };
+        let surface = clip_to_box(vec![a, b, c], half_size);
+        if surface.len() < 3 {
+            continue;
+        }
+        buffers.project(svg_triangles, &surface, a, normal, decal.offset);
+    }
+    buffers
+}
+
+/// A triangle of a tessellated [`Svg`], in the xy-plane of the decal box.
+struct SvgTriangle {
+    points: [Vec2; 3],
+    colors: [Vec4; 3],
+    area: f32,
+    min: Vec2,
+    max: Vec2,
+}
+
+impl SvgTriangle {
+    /// Collects the triangles of the mesh of an [`Svg`] in paint order, with the `view_box`
+    /// of the [`Svg`] stretched over `box_size`.
+    fn from_mesh(mesh: &Mesh, view_box: ViewBox, box_size: Vec2) -> Option<Vec<Self>> {
+        if view_box.w <= 0.0 || view_box.h <= 0.0 {
+            return None;
+        }
+        // The mesh has either the standard or the compact attributes
+        let positions: Vec<Vec2> = match positions(mesh) {
+            Some(positions) => positions.iter().map(|p| Vec2::from_slice(p)).collect(),
+            None => match mesh.attribute(ATTRIBUTE_POSITION_2D)? {
+                VertexAttributeValues::Float32x2(positions) => {
+                    positions.iter().copied().map(Vec2::from).collect()
+                }
+                _ => return None,
+            },
+        };
+        let colors: Vec<Vec4> = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+            Some(VertexAttributeValues::Float32x4(colors)) => {
+                colors.iter().copied().map(Vec4::from).collect()
+            }
+            _ => match mesh.attribute(ATTRIBUTE_COLOR_PACKED)? {
+                VertexAttributeValues::Unorm8x4(colors) => colors
+                    .iter()
+                    .map(|&[r, g, b, a]| {
+                        Vec4::from(Color::rgba_u8(r, g, b, a).as_linear_rgba_f32())
+                    })
+                    .collect(),
+                _ => return None,
+            },
+        };
+        // The viewBox spans from its top left corner to its bottom right corner, with the
+        // y-axis of the mesh flipped
+        let offset = Vec2::new(view_box.x as f32, -view_box.y as f32);
+        let size = Vec2::new(view_box.w as f32, view_box.h as f32);
+        let point = |i: usize| ((positions[i] - offset) / size + Vec2::new(-0.5, 0.5)) * box_size;
+
+        let indices: Vec<usize> = mesh.indices()?.iter().collect();
+        let triangles = indices
+            .chunks_exact(3)
+            .filter_map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| point(triangle[i]));
+                let area = (b - a).perp_dot(c - a);
+                (area.abs() > f32::EPSILON).then(|| Self {
+                    points: [a, b, c],
+                    colors: [0, 1, 2].map(|i| colors[triangle[i]]),
+                    area,
+                    min: a.min(b).min(c),
+                    max: a.max(b).max(c),
+                })
+            })
+            .collect();
+        Some(triangles)
+    }
+
+    /// The color at `point`, interpolated from the corners.
+    fn color_at(&self, point: Vec2) -> Vec4 {
+        let [first, second, third] = self.points;
+        let first_weight = (third - second).perp_dot(point - second) / self.area;
+        let second_weight = (first - third).perp_dot(point - third) / self.area;
+        let third_weight = 1.0 - first_weight - second_weight;
+        self.colors[0] * first_weight
+            + self.colors[1] * second_weight
+            + self.colors[2] * third_weight
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct DecalBuffers {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl DecalBuffers {
+    /// Adds the parts of the SVG triangles which lie on `surface`, a convex polygon in the
+    /// plane through `origin` with `normal`, in counter-clockwise order seen from the
+    /// projection.
+    fn project(
+        &mut self,
+        svg_triangles: &[SvgTriangle],
+        surface: &[Vec3],
+        origin: Vec3,
+        normal: Vec3,
+        offset: f32,
+    ) {
+        let outline: Vec<Vec2> = surface.iter().map(|p| p.xy()).collect();
+        let min = outline
+            .iter()
+            .copied()
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let max = outline
+            .iter()
+            .copied()
+            .reduce(Vec2::max)
+            .unwrap_or_default();
+        let height = |p: Vec2| origin.z - normal.xy().dot(p - origin.xy()) / normal.z + offset;
+
+        for triangle in svg_triangles {
+            if triangle.max.cmplt(min).any() || triangle.min.cmpgt(max).any() {
+                continue;
+            }
+            let mut polygon = triangle.points.to_vec();
+            for (start, end) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+                polygon = clip(&polygon, |p| (*end - *start).perp_dot(*p - *start));
+                if polygon.len() < 3 {
+                    break;
+                }
+            }
+            if polygon.len() < 3 {
+                continue;
+            }
+            // Lyon doesn't guarantee the winding order of the SVG triangles
+            if signed_area(&polygon) < 0.0 {
+                polygon.reverse();
+            }
+
+            let first = self.positions.len() as u32;
+            for point in &polygon {
+                self.positions.push(point.extend(height(*point)).to_array());
+                self.colors.push(triangle.color_at(*point).to_array());
+            }
+            for i in 1..polygon.len() as u32 - 1 {
+                self.indices.extend([first, first + i, first + i + 1]);
+            }
+        }
+    }
+
+    /// Appends the vertices and triangles of `other`.
+    fn extend(&mut self, other: &Self) {
+        let first = self.positions.len() as u32;
+        self.positions.extend_from_slice(&other.positions);
+        self.colors.extend_from_slice(&other.colors);
+        self.indices
+            .extend(other.indices.iter().map(|index| first + index));
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+fn positions(mesh: &Mesh) -> Option<&Vec<[f32; 3]>> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+        VertexAttributeValues::Float32x3(positions) => Some(positions),
+        _ => None,
+    }
+}
+
+/// All triangles of a triangle list mesh.
+fn triangles(mesh: &Mesh) -> Vec<[Vec3A; 3]> {
+    let Some(positions) = positions(mesh) else {
+        return Vec::new();
+    };
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return Vec::new();
+    }
+    let indices: Vec<usize> = mesh.indices().map_or_else(
+        || (0..positions.len()).collect(),
+        |indices| indices.iter().collect(),
+    );
+    indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let point = |i: usize| positions.get(triangle[i]).copied().map(Vec3A::from);
+            Some([point(0)?, point(1)?, point(2)?])
+        })
+        .collect()
+}
+
+/// Whether the bounds of a mesh, transformed into the space of the decal, overlap the box.
+fn overlaps_box(aabb: &Aabb, to_decal: &Affine3A, half_size: Vec3) -> bool {
+    let center = to_decal.transform_point3a(aabb.center);
+    let matrix = to_decal.matrix3;
+    let extents = Mat3A::from_cols(
+        matrix.x_axis.abs(),
+        matrix.y_axis.abs(),
+        matrix.z_axis.abs(),
+    ) * aabb.half_extents;
+    (center - extents).cmple(half_size.into()).all()
+        && (center + extents).cmpge((-half_size).into()).all()
+}
+
+/// Clips a convex polygon to the box centered at the origin.
+fn clip_to_box(mut polygon: Vec<Vec3>, half_size: Vec3) -> Vec<Vec3> {
+    for axis in 0..3 {
+        polygon = clip(&polygon, |p| p[axis] + half_size[axis]);
+        polygon = clip(&polygon, |p| half_size[axis] - p[axis]);
+    }
+    polygon
+}
+
+/// Clips a convex polygon to the half-space where `distance` is not negative.
+fn clip<P>(polygon: &[P], distance: impl Fn(&P) -> f32) -> Vec<P>
+where
+    P: Copy
+        + std::ops::Add<Output = P>
+        + std::ops::Sub<Output = P>
+        + std::ops::Mul<f32, Output = P>,
+{
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (start, end) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (d_start, d_end) = (distance(start), distance(end));
+        if d_start >= 0.0 {
+            clipped.push(*start);
+        }
+        if (d_start >= 0.0) != (d_end >= 0.0) {
+            clipped.push(*start + (*end - *start) * (d_start / (d_start - d_end)));
+        }
+    }
+    clipped
+}
+
+fn signed_area(polygon: &[Vec2]) -> f32 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        math::{Affine3A, Vec2, Vec3},
+        transform::components::Transform,
+    };
+
+    use super::*;
+
+    /// A square mesh in the xy-plane with the given corners, facing +z.
+    fn quad(min: Vec2, max: Vec2, z: f32) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        let positions = vec![
+            [min.x, min.y, z],
+            [max.x, min.y, z],
+            [max.x, max.y, z],
+            [min.x, max.y, z],
+        ];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0, 0.0, 0.0, 1.0]; 4]);
+        mesh.set_indices(Some(Indices::U32(vec![0, 1, 2, 0, 2, 3])));
+        mesh
+    }
+
+    #[test]
+    fn svg_triangles_are_normalized_by_view_box() {
+        let view_box = ViewBox {
+            x: 10.0,
+            y: 20.0,
+            w: 100.0,
+            h: 50.0,
+        };
+        // The mesh of the viewBox, with the y-axis flipped
+        let mesh = quad(Vec2::new(10.0, -70.0), Vec2::new(110.0, -20.0), 0.0);
+        let triangles = SvgTriangle::from_mesh(&mesh, view_box, Vec2::new(2.0, 1.0)).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[0].min, Vec2::new(-1.0, -0.5));
+        assert_eq!(triangles[0].max, Vec2::new(1.0, 0.5));
+
+        let empty = ViewBox { w: 0.0, ..view_box };
+        assert!(SvgTriangle::from_mesh(&mesh, empty, Vec2::ONE).is_none());
+    }
+
+    #[test]
+    fn clips_polygons_to_box() {
+        let triangle = vec![
+            Vec3::new(-2.0, -0.5, 0.0),
+            Vec3::new(0.5, -0.5, 0.0),
+            Vec3::new(0.5, 2.0, 0.0),
+        ];
+        let clipped = clip_to_box(triangle, Vec3::ONE);
+        assert!(clipped
+            .iter()
+            .all(|p| p.abs().cmple(Vec3::ONE + 1e-6).all()));
+        assert!(clipped.len() > 3);
+
+        let outside = vec![
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+        ];
+        assert!(clip_to_box(outside, Vec3::ONE).is_empty());
+    }
+
+    #[test]
+    fn mirrored_and_back_facing_surfaces_are_skipped() {
+        let svg = [SvgTriangle {
+            points: [
+                Vec2::new(-1.0, -1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(0.0, 1.0),
+            ],
+            colors: [Vec4::ONE; 3],
+            area: 4.0,
+            min: Vec2::splat(-1.0),
+            max: Vec2::ONE,
+        }];
+        let decal = SvgDecal {
+            size: Vec3::splat(4.0),
+            ..Default::default()
+        };
+        let ground = quad(Vec2::splat(-1.0), Vec2::ONE, 0.0);
+        let facing = project(&svg, &ground, &Affine3A::IDENTITY, None, &decal);
+        assert!(!facing.indices.is_empty());
+        assert!(facing
+            .positions
+            .iter()
+            .all(|p| (p[2] - decal.offset).abs() < 1e-6));
+
+        let flipped = Affine3A::from_rotation_x(std::f32::consts::PI);
+        assert!(project(&svg, &ground, &flipped, None, &decal)
+            .indices
+            .is_empty());
+        let mirrored = Affine3A::from_scale(Vec3::new(-1.0, 1.0, 1.0));
+        assert!(!project(&svg, &ground, &mirrored, None, &decal)
+            .indices
+            .is_empty());
+    }
+
+    #[test]
+    fn decals_are_clipped_at_the_box_boundaries() {
+        // A triangle which covers the whole box
+        let svg = [SvgTriangle {
+            points: [
+                Vec2::new(-10.0, -10.0),
+                Vec2::new(10.0, -10.0),
+                Vec2::new(0.0, 10.0),
+            ],
+            colors: [Vec4::ONE; 3],
+            area: 400.0,
+            min: Vec2::splat(-10.0),
+            max: Vec2::splat(10.0),
+        }];
+        let decal = SvgDecal {
+            size: Vec3::splat(2.0),
+            offset: 0.0,
+            ..Default::default()
+        };
+        let max_abs = |buffers: &DecalBuffers| {
+            buffers
+                .positions
+                .iter()
+                .fold(Vec3::ZERO, |max, p| max.max(Vec3::from(*p).abs()))
+        };
+        let ground = quad(Vec2::splat(-3.0), Vec2::splat(3.0), 0.0);
+
+        // The sides of the box clip a surface which is larger than the box
+        let flat = project(&svg, &ground, &Affine3A::IDENTITY, None, &decal);
+        assert!(max_abs(&flat).abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-5));
+
+        // The top and the bottom of the box clip a slope which leaves the box
+        let slope = Affine3A::from_rotation_y(std::f32::consts::FRAC_PI_3);
+        let sloped = project(&svg, &ground, &slope, None, &decal);
+        let max = max_abs(&sloped);
+        assert!((max.z - 1.0).abs() < 1e-5);
+        assert!((max.x - 1.0 / 3.0f32.sqrt()).abs() < 1e-5);
+        assert!((max.y - 1.0).abs() < 1e-5);
+    }
+
+    fn decal_mesh(app: &mut App) -> Option<Entity> {
+        let mut query = app.world.query_filtered::<Entity, With<SvgDecalMesh>>();
+        let children: Vec<_> = query.iter(&app.world).collect();
+        assert!(children.len() <= 1);
+        children.first().copied()
+    }
+
+    fn move_by(app: &mut App, entity: Entity, translation: Vec3) {
+        let mut transform = app.world.get_mut::<GlobalTransform>(entity).unwrap();
+        *transform = Transform::from_translation(translation) * *transform;
+    }
+
+    #[test]
+    fn only_bakes_targets_moved_relative_to_the_decal() {
+        let mut app = App::new();
+        app.add_event::<AssetEvent<Svg>>()
+            .init_resource::<Assets<Svg>>()
+            .init_resource::<Assets<Mesh>>()
+            .add_systems(Update, bake_svg_decals);
+
+        let mut meshes = app.world.resource_mut::<Assets<Mesh>>();
+        let svg_mesh = meshes.add(quad(Vec2::new(0.0, -10.0), Vec2::new(10.0, 0.0), 0.0));
+        let target_mesh = meshes.add(quad(Vec2::splat(-0.5), Vec2::splat(0.5), 0.0));
+        let svg = app.world.resource_mut::<Assets<Svg>>().add(Svg {
+            size: Vec2::splat(10.0),
+            view_box: ViewBox {
+                x: 0.0,
+                y: 0.0,
+                w: 10.0,
+                h: 10.0,
+            },
+            mesh: svg_mesh,
+            ..Default::default()
+        });
+        let decal = app
+            .world
+            .spawn((
+                SvgDecal {
+                    svg,
+                    size: Vec3::new(4.0, 4.0, 2.0),
+                    ..Default::default()
+                },
+                GlobalTransform::IDENTITY,
+            ))
+            .id();
+        let [left, right] = [-1.0, 1.0].map(|x| {
+            app.world
+                .spawn((target_mesh.clone(), GlobalTransform::from_xyz(x, 0.0, -0.5)))
+                .id()
+        });
+        app.update();
+        let first = decal_mesh(&mut app).expect("the decal is baked");
+        let baked =
+            |app: &App, target| app.world.get::<SvgDecalTargets>(decal).unwrap().0[&target].0;
+        let right_to_decal = baked(&app, right);
+
+        // Moving everything together doesn't change the decal
+        for entity in [decal, left, right] {
+            move_by(&mut app, entity, Vec3::new(5.0, 0.0, 0.0));
+        }
+        app.update();
+        assert_eq!(decal_mesh(&mut app), Some(first));
+
+        // Only the moved target is projected again
+        move_by(&mut app, left, Vec3::new(0.0, 0.5, 0.0));
+        app.update();
+        let second = decal_mesh(&mut app).expect("the decal is baked again");
+        assert_ne!(second, first);
+        assert_eq!(baked(&app, right), right_to_decal);
+        assert_ne!(baked(&app, left).translation, Vec3A::new(-1.0, 0.0, -0.5));
+
+        // Removed targets are removed from the decal
+        app.world.despawn(left);
+        app.update();
+        let targets = &app.world.get::<SvgDecalTargets>(decal).unwrap().0;
+        assert_eq!(targets.keys().collect::<Vec<_>>(), [&right]);
+        assert_ne!(decal_mesh(&mut app), Some(second));
+    }
+}
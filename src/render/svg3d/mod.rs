@@ -1,10 +1,20 @@
 use bevy::{asset::Handle, render::render_resource::Shader};
 
+mod billboard;
 mod bundle;
+mod decal;
 mod plugin;
 
 /// Handle to the custom shader with a unique random ID
-pub const SVG_3D_SHADER_HANDLE:  Handle<Shader> = Handle::weak_from_u128(8_514_826_640_451_853_414);
+pub const SVG_3D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8_514_826_640_451_853_414);
+/// Handle to the prepass shader, which also draws the shadows
+pub const SVG_3D_PREPASS_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(2_806_351_079_425_178_993);
+/// Handle to the vertex shader of all 3D SVGs, which also turns billboards towards the camera
+pub const SVG_3D_VERTEX_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(2_806_351_079_425_179_017);
 
-pub use bundle::Svg3dBundle;
+pub use billboard::{SvgBillboard, SvgBillboardMode};
+pub use bundle::{Svg3d, Svg3dBundle};
+pub use decal::{SvgDecal, SvgDecalMesh};
 pub use plugin::RenderPlugin;
@@ -1,13 +1,43 @@
 use bevy::{
-    app::{App, Plugin},
-    asset::{load_internal_asset, AssetApp},
-    pbr::{Material, MaterialPlugin},
-    render::render_resource::{Shader, ShaderRef},
+    app::{App, Plugin, PostUpdate},
+    asset::{load_internal_asset, AssetApp, Assets, Handle},
+    ecs::{
+        component::Component,
+        event::EventReader,
+        query::{Has, With},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res},
+    },
+    pbr::{
+        extract_meshes, AlphaMode, Material, MaterialPipeline, MaterialPipelineKey, MaterialPlugin,
+        NotShadowCaster,
+    },
+    render::{
+        mesh::{Mesh, MeshVertexBufferLayout},
+        render_resource::{
+            Face, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError,
+        },
+        ExtractSchedule, RenderApp,
+    },
+    transform::TransformSystem,
 };
 
-use crate::svg::Svg;
+use crate::{
+    plugin::SvgSet,
+    render::material::SvgMaterialKey,
+    status::SvgEvent,
+    stroke,
+    svg::{
+        Svg, SvgAlphaMode, SvgCullMode, SvgLighting, ATTRIBUTE_COLOR_PACKED, ATTRIBUTE_PATH_INDEX,
+        ATTRIBUTE_POSITION_2D, ATTRIBUTE_STROKE_NORMAL,
+    },
+    Convert,
+};
 
-use super::SVG_3D_SHADER_HANDLE;
+use super::{
+    billboard, decal, Svg3d, SvgBillboard, SvgBillboardMode, SvgDecal, SvgDecalMesh,
+    SVG_3D_PREPASS_SHADER_HANDLE, SVG_3D_SHADER_HANDLE, SVG_3D_VERTEX_SHADER_HANDLE,
+};
 
 /// Plugin that renders [`Svg`](crate::svg::Svg)s in 2D
 pub struct RenderPlugin;
@@ -15,9 +45,77 @@ pub struct RenderPlugin;
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
         load_internal_asset!(app, SVG_3D_SHADER_HANDLE, "svg_3d.wgsl", Shader::from_wgsl);
+        load_internal_asset!(
+            app,
+            SVG_3D_PREPASS_SHADER_HANDLE,
+            "svg_3d_prepass.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SVG_3D_VERTEX_SHADER_HANDLE,
+            "svg_3d_vertex.wgsl",
+            Shader::from_wgsl
+        );
 
         app.add_plugins(MaterialPlugin::<Svg>::default())
-            .register_asset_reflect::<Svg>();
+            .register_asset_reflect::<Svg>()
+            .register_type::<Svg3d>()
+            .register_type::<SvgBillboard>()
+            .register_type::<SvgBillboardMode>()
+            .register_type::<SvgDecal>()
+            .register_type::<SvgDecalMesh>()
+            .add_systems(
+                PostUpdate,
+                (
+                    (update_shadow_casters, billboard::update_billboard_culling)
+                        .after(SvgSet::LinkMeshes),
+                    decal::bake_svg_decals.after(TransformSystem::TransformPropagate),
+                ),
+            );
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                ExtractSchedule,
+                (
+                    billboard::extract_billboards,
+                    stroke::extract_stroke_widths_3d,
+                )
+                    .after(extract_meshes),
+            );
+        }
+    }
+}
+
+/// Marks a [`NotShadowCaster`] which was added because the [`Svg`] doesn't cast shadows.
+#[derive(Component)]
+pub(crate) struct SvgNotShadowCaster;
+
+type ShadowCasters = (Has<NotShadowCaster>, Has<SvgNotShadowCaster>);
+
+/// Adds a [`NotShadowCaster`] to the 3D entities whose [`Svg`] doesn't cast shadows, and
+/// removes it again if the [`Svg`] is modified to cast them. A [`NotShadowCaster`] which was
+/// added by the user is never removed.
+fn update_shadow_casters(
+    mut commands: Commands,
+    svgs: Res<Assets<Svg>>,
+    mut svg_events: EventReader<SvgEvent>,
+    query: Query<ShadowCasters, With<Handle<Mesh>>>,
+) {
+    for event in svg_events.read() {
+        let (SvgEvent::Ready { entity, handle } | SvgEvent::Reloaded { entity, handle }) = event;
+        let (Some(svg), Ok((not_caster, added))) = (svgs.get(handle), query.get(*entity)) else {
+            continue;
+        };
+        if !svg.render_3d.cast_shadows && !not_caster {
+            commands
+                .entity(*entity)
+                .insert((NotShadowCaster, SvgNotShadowCaster));
+        } else if svg.render_3d.cast_shadows && added {
+            commands
+                .entity(*entity)
+                .remove::<(NotShadowCaster, SvgNotShadowCaster)>();
+        }
     }
 }
 
@@ -25,4 +123,236 @@ impl Material for Svg {
     fn fragment_shader() -> ShaderRef {
         SVG_3D_SHADER_HANDLE.into()
     }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        SVG_3D_PREPASS_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.render_3d.alpha_mode.convert()
+    }
+
+    fn depth_bias(&self) -> f32 {
+        self.render_3d.depth_bias
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        specialize(descriptor, layout, key.bind_group_data)
+    }
+}
+
+/// Specializes the main, prepass and shadow pipelines of the [`Svg`] material for the
+/// settings of the [`Svg`] and the attributes of its mesh.
+fn specialize(
+    descriptor: &mut RenderPipelineDescriptor,
+    layout: &MeshVertexBufferLayout,
+    key: SvgMaterialKey,
+) -> Result<(), SpecializedMeshPipelineError> {
+    descriptor.primitive.cull_mode = key.cull_mode.convert();
+    // The prepass and the shadows always need the depth of the SVG
+    let is_prepass = descriptor
+        .vertex
+        .shader_defs
+        .contains(&"PREPASS_PIPELINE".into());
+    if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+        if !is_prepass {
+            depth_stencil.depth_write_enabled &= key.depth_write;
+            depth_stencil.bias.constant = key.depth_bias;
+        }
+    }
+    // Any entity can be a billboard, so all SVGs use the vertex shader of this crate
+    descriptor.vertex.shader = SVG_3D_VERTEX_SHADER_HANDLE;
+    if layout.contains(ATTRIBUTE_PATH_INDEX) {
+        let path_index = layout.get_layout(&[ATTRIBUTE_PATH_INDEX.at_shader_location(9)])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(path_index.attributes);
+        descriptor.vertex.shader_defs.push("SVG_PATH_INDEX".into());
+        if key.path_styles {
+            descriptor.vertex.shader_defs.push("SVG_PATH_STYLES".into());
+        }
+    }
+    if layout.contains(ATTRIBUTE_STROKE_NORMAL) {
+        let stroke = layout.get_layout(&[ATTRIBUTE_STROKE_NORMAL.at_shader_location(10)])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(stroke.attributes);
+        descriptor.vertex.shader_defs.push("SVG_STROKE".into());
+    }
+    // The alpha discard of the prepass needs the vertex colors, but not every prepass
+    // pipeline of Bevy passes them
+    let needs_colors = is_prepass && layout.contains(Mesh::ATTRIBUTE_COLOR);
+    if needs_colors
+        && !descriptor.vertex.buffers[0]
+            .attributes
+            .iter()
+            .any(|attribute| attribute.shader_location == 6)
+    {
+        let color = layout.get_layout(&[Mesh::ATTRIBUTE_COLOR.at_shader_location(6)])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(color.attributes);
+    }
+    // Bevy only knows the standard position and color attributes
+    let is_compact = layout.contains(ATTRIBUTE_POSITION_2D);
+    if is_compact {
+        let color_location = if is_prepass { 6 } else { 5 };
+        let compact = layout.get_layout(&[
+            ATTRIBUTE_POSITION_2D.at_shader_location(0),
+            ATTRIBUTE_COLOR_PACKED.at_shader_location(color_location),
+        ])?;
+        descriptor.vertex.buffers[0]
+            .attributes
+            .extend(compact.attributes);
+        descriptor.vertex.shader_defs.push("SVG_COMPACT".into());
+    }
+    let vertex_colors = "VERTEX_COLORS".into();
+    if (is_compact || needs_colors) && !descriptor.vertex.shader_defs.contains(&vertex_colors) {
+        descriptor.vertex.shader_defs.push(vertex_colors.clone());
+    }
+    if let Some(fragment) = descriptor.fragment.as_mut() {
+        if (is_compact || needs_colors) && !fragment.shader_defs.contains(&vertex_colors) {
+            fragment.shader_defs.push(vertex_colors);
+        }
+        if key.alpha_add {
+            fragment.shader_defs.push("SVG_ALPHA_ADD".into());
+        }
+        if key.lighting == SvgLighting::Lit {
+            fragment.shader_defs.push("SVG_LIT".into());
+        }
+    }
+    Ok(())
+}
+
+impl Convert<AlphaMode> for SvgAlphaMode {
+    fn convert(self) -> AlphaMode {
+        match self {
+            Self::Opaque => AlphaMode::Opaque,
+            Self::Mask(cutoff) => AlphaMode::Mask(cutoff),
+            Self::Blend => AlphaMode::Blend,
+            Self::Premultiplied => AlphaMode::Premultiplied,
+            Self::Add => AlphaMode::Add,
+            Self::Multiply => AlphaMode::Multiply,
+        }
+    }
+}
+
+impl Convert<Option<Face>> for SvgCullMode {
+    fn convert(self) -> Option<Face> {
+        match self {
+            Self::Back => Some(Face::Back),
+            Self::Front => Some(Face::Front),
+            Self::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{
+        CompareFunction, DepthBiasState, DepthStencilState, FragmentState, MultisampleState,
+        PrimitiveState, StencilState, TextureFormat, VertexState,
+    };
+
+    use super::*;
+
+    fn descriptor(layout: &MeshVertexBufferLayout, prepass: bool) -> RenderPipelineDescriptor {
+        let shader_defs = if prepass {
+            vec!["PREPASS_PIPELINE".into()]
+        } else {
+            Vec::new()
+        };
+        let position = layout
+            .get_layout(&[Mesh::ATTRIBUTE_POSITION.at_shader_location(0)])
+            .unwrap();
+        RenderPipelineDescriptor {
+            label: None,
+            layout: Vec::new(),
+            push_constant_ranges: Vec::new(),
+            vertex: VertexState {
+                shader: Handle::default(),
+                shader_defs: shader_defs.clone(),
+                entry_point: "vertex".into(),
+                buffers: vec![position],
+            },
+            primitive: PrimitiveState::default(),
+            depth_stencil: Some(DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState::default(),
+            fragment: Some(FragmentState {
+                shader: Handle::default(),
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: Vec::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn settings_specialize_the_pipeline() {
+        let mut svg = Svg::default();
+        svg.render_3d.alpha_mode = SvgAlphaMode::Add;
+        svg.render_3d.cull_mode = SvgCullMode::None;
+        svg.render_3d.depth_bias = 2.0;
+        svg.render_3d.lighting = SvgLighting::Lit;
+        assert_eq!(svg.alpha_mode(), AlphaMode::Add);
+        assert_eq!(Material::depth_bias(&svg), 2.0);
+
+        let layout = svg.tessellate().get_mesh_vertex_buffer_layout();
+        let mut main = descriptor(&layout, false);
+        specialize(&mut main, &layout, SvgMaterialKey::from(&svg)).unwrap();
+        assert_eq!(main.primitive.cull_mode, None);
+        let depth_stencil = main.depth_stencil.unwrap();
+        assert!(!depth_stencil.depth_write_enabled);
+        assert_eq!(depth_stencil.bias.constant, 2);
+        let fragment_defs = main.fragment.unwrap().shader_defs;
+        assert!(fragment_defs.contains(&"SVG_ALPHA_ADD".into()));
+        assert!(fragment_defs.contains(&"SVG_LIT".into()));
+
+        let mut prepass = descriptor(&layout, true);
+        specialize(&mut prepass, &layout, SvgMaterialKey::from(&svg)).unwrap();
+        let depth_stencil = prepass.depth_stencil.unwrap();
+        assert!(
+            depth_stencil.depth_write_enabled,
+            "the prepass always writes the depth"
+        );
+        assert_eq!(depth_stencil.bias.constant, 0);
+    }
+
+    #[test]
+    fn prepass_has_vertex_colors() {
+        let layout = Svg::default().tessellate().get_mesh_vertex_buffer_layout();
+        let key = SvgMaterialKey::from(&Svg::default());
+        let mut prepass = descriptor(&layout, true);
+        specialize(&mut prepass, &layout, key).unwrap();
+        let colors = prepass.vertex.buffers[0]
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.shader_location == 6)
+            .count();
+        assert_eq!(colors, 1);
+        assert!(prepass.vertex.shader_defs.contains(&"VERTEX_COLORS".into()));
+        assert!(prepass
+            .fragment
+            .unwrap()
+            .shader_defs
+            .contains(&"VERTEX_COLORS".into()));
+
+        let mut main = descriptor(&layout, false);
+        specialize(&mut main, &layout, key).unwrap();
+        assert!(main.vertex.buffers[0]
+            .attributes
+            .iter()
+            .all(|attribute| attribute.shader_location != 6));
+    }
 }
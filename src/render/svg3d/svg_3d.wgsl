@@ -1,11 +1,17 @@
 #import bevy_pbr::{
     forward_io::{VertexOutput, FragmentOutput}
-    mesh_types::Mesh,
     mesh_view_bindings,
 }
+#import bevy_svg::svg_material::material
 
-@group(2) @binding(0)
-var<uniform> mesh: Mesh;
+#ifdef SVG_LIT
+#import bevy_pbr::{
+    mesh_bindings::mesh,
+    pbr_functions,
+    pbr_types,
+}
+#import bevy_core_pipeline::tonemapping::tone_mapping
+#endif
 
 @fragment
 fn fragment(
@@ -14,5 +20,38 @@ fn fragment(
 ) -> FragmentOutput {
     var out: FragmentOutput;
     out.color = in.color;
+#ifdef MAY_DISCARD
+    if out.color.a < material.alpha_cutoff {
+        discard;
+    }
+    out.color.a = 1.0;
+#endif
+#ifdef SVG_LIT
+    // The normal of the vertex shader faces the viewer on both sides
+    var pbr_input = pbr_types::pbr_input_new();
+    pbr_input.material.base_color = out.color;
+    pbr_input.flags = mesh[in.instance_index].flags;
+    pbr_input.is_orthographic = mesh_view_bindings::view.projection[3].w == 1.0;
+    pbr_input.V = pbr_functions::calculate_view(in.world_position, pbr_input.is_orthographic);
+    pbr_input.frag_coord = in.position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = pbr_functions::prepare_world_normal(in.world_normal, true, is_front);
+    pbr_input.N = normalize(pbr_input.world_normal);
+    out.color = pbr_functions::apply_pbr_lighting(pbr_input);
+#ifdef TONEMAP_IN_SHADER
+    out.color = tone_mapping(out.color, mesh_view_bindings::view.color_grading);
+#endif
+#endif
+    // The colors of an SVG are never premultiplied
+#ifdef BLEND_PREMULTIPLIED_ALPHA
+#ifdef SVG_ALPHA_ADD
+    out.color = vec4<f32>(out.color.rgb * out.color.a, 0.0);
+#else
+    out.color = vec4<f32>(out.color.rgb * out.color.a, out.color.a);
+#endif
+#endif
+#ifdef BLEND_MULTIPLY
+    out.color = vec4<f32>(out.color.rgb * out.color.a, out.color.a);
+#endif
     return out;
 }
@@ -0,0 +1,48 @@
+#import bevy_pbr::{
+    prepass_bindings::previous_view_proj,
+    prepass_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::view,
+}
+
+#import bevy_svg::svg_material::material
+
+// Shadows and the depth prepass only keep the pixels which are visible in the main pass,
+// with the vertex colors which the specialization of the material adds to every prepass.
+// The normal and deferred prepasses are not supported, because the mesh has no normals.
+fn alpha_discard(in: VertexOutput) {
+#ifdef MAY_DISCARD
+#ifdef VERTEX_COLORS
+    if in.color.a < material.alpha_cutoff {
+        discard;
+    }
+#endif
+#endif
+}
+
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(in: VertexOutput) -> FragmentOutput {
+    alpha_discard(in);
+
+    var out: FragmentOutput;
+
+#ifdef DEPTH_CLAMP_ORTHO
+    out.frag_depth = in.clip_position_unclamped.z;
+#endif
+
+#ifdef MOTION_VECTOR_PREPASS
+    let clip_position_t = view.unjittered_view_proj * in.world_position;
+    let clip_position = clip_position_t.xy / clip_position_t.w;
+    let previous_clip_position_t = previous_view_proj * in.previous_world_position;
+    let previous_clip_position = previous_clip_position_t.xy / previous_clip_position_t.w;
+    out.motion_vector = (clip_position - previous_clip_position) * vec2<f32>(0.5, -0.5);
+#endif
+
+    return out;
+}
+#else
+@fragment
+fn fragment(in: VertexOutput) {
+    alpha_discard(in);
+}
+#endif
@@ -0,0 +1,257 @@
+#import bevy_pbr::{
+    mesh_bindings::mesh,
+    mesh_functions,
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+}
+#import bevy_render::instance_index::get_instance_index
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::prepass_io::VertexOutput
+#else
+#import bevy_pbr::forward_io::VertexOutput
+#endif
+
+#import bevy_svg::svg_material::{
+    entity_stroke, material, path_color, path_hidden, path_stroke_width, unpack_color,
+}
+
+// Keep in sync with `MESH_FLAGS_BILLBOARD` and `SvgBillboardMode` in billboard.rs
+const SVG_MESH_FLAGS_BILLBOARD_BIT: u32 = 16777216u;
+const BILLBOARD_FULL: u32 = 0u;
+const BILLBOARD_CYLINDRICAL: u32 = 1u;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+#ifdef SVG_COMPACT
+    @location(0) position: vec2<f32>,
+#else
+    @location(0) position: vec3<f32>,
+#endif
+#ifdef VERTEX_NORMALS
+#ifdef PREPASS_PIPELINE
+    @location(3) normal: vec3<f32>,
+#else
+    @location(1) normal: vec3<f32>,
+#endif
+#endif
+#ifdef VERTEX_UVS
+#ifdef PREPASS_PIPELINE
+    @location(1) uv: vec2<f32>,
+#else
+    @location(2) uv: vec2<f32>,
+#endif
+#endif
+#ifdef VERTEX_COLORS
+#ifdef PREPASS_PIPELINE
+    @location(6) color: vec4<f32>,
+#else
+    @location(5) color: vec4<f32>,
+#endif
+#endif
+#ifdef SVG_PATH_INDEX
+    @location(9) path_index: u32,
+#endif
+#ifdef SVG_STROKE
+    // The normal, the half width and the side of the stroke. A negative half width is given
+    // in pixels on screen and the vertex lies on the centerline.
+    @location(10) stroke: vec4<f32>,
+#endif
+};
+
+fn is_billboard(instance_index: u32) -> bool {
+    let flags = mesh[get_instance_index(instance_index)].flags;
+    return (flags & SVG_MESH_FLAGS_BILLBOARD_BIT) != 0u;
+}
+
+struct Billboard {
+    world_position: vec4<f32>,
+    world_normal: vec3<f32>,
+};
+
+// Turns a vertex around the pivot towards the view. The model matrix of a billboard has the
+// scale, the mode and whether the size is constant on screen, and the pivot in mesh
+// coordinates in place of its rotation, see `SvgBillboard::transform` in billboard.rs.
+fn billboard(model: mat4x4<f32>, position: vec3<f32>) -> Billboard {
+    let scale = model[0].xyz;
+    let settings = vec4<f32>(model[1].xy, model[2].xy);
+    // The origin offset was added to the translation along the world axes
+    let pivot = model[3].xyz + scale * vec3<f32>(settings.zw, 0.0);
+    let is_orthographic = view.projection[3].w == 1.0;
+
+    var right = view.view[0].xyz;
+    var up = view.view[1].xyz;
+    var forward = view.view[2].xyz;
+    let mode = u32(settings.x);
+    if mode == BILLBOARD_FULL {
+        if !is_orthographic {
+            forward = normalize(view.world_position - pivot);
+        }
+        right = normalize(cross(up, forward));
+        up = cross(forward, right);
+    } else if mode == BILLBOARD_CYLINDRICAL {
+        var to_view = forward;
+        if !is_orthographic {
+            to_view = view.world_position - pivot;
+        }
+        to_view.y = 0.0;
+        // Seen from straight above or below, the billboard faces down the screen
+        if dot(to_view, to_view) < 1e-8 {
+            to_view = vec3<f32>(-up.x, 0.0, -up.z) * sign(forward.y);
+        }
+        up = vec3<f32>(0.0, 1.0, 0.0);
+        forward = normalize(to_view);
+        right = cross(up, forward);
+    }
+
+    var size = scale;
+    if settings.y > 0.5 {
+        // One unit of the scaled SVG covers one pixel of the viewport at any distance
+        let clip_w = (view.view_proj * vec4<f32>(pivot, 1.0)).w;
+        size *= 2.0 * clip_w / (view.projection[1][1] * view.viewport.w);
+    }
+
+    let local = (position - vec3<f32>(settings.zw, 0.0)) * size;
+    var out: Billboard;
+    out.world_position = vec4<f32>(pivot + right * local.x + up * local.y + forward * local.z, 1.0);
+    out.world_normal = forward;
+    return out;
+}
+
+fn local_position(vertex: Vertex) -> vec3<f32> {
+#ifdef SVG_COMPACT
+    return vec3<f32>(vertex.position, 0.0);
+#else
+    return vertex.position;
+#endif
+}
+
+#ifdef SVG_STROKE
+// Multiplies the width of the stroke of the vertex
+fn stroke_width(vertex: Vertex) -> f32 {
+#ifdef SVG_PATH_INDEX
+    return material.stroke_width * path_stroke_width(vertex.path_index);
+#else
+    return material.stroke_width;
+#endif
+}
+
+// The stroke normal of the vertex with the `SvgStrokeWidth` of the entity
+fn stroke_normal(vertex: Vertex) -> vec4<f32> {
+    let flags = mesh[get_instance_index(vertex.instance_index)].flags;
+    return entity_stroke(vertex.stroke, flags);
+}
+#endif
+
+fn local_to_world(model: mat4x4<f32>, vertex: Vertex, local: vec3<f32>) -> vec4<f32> {
+    if is_billboard(vertex.instance_index) {
+        return billboard(model, local).world_position;
+    }
+    return mesh_functions::mesh_position_local_to_world(model, vec4<f32>(local, 1.0));
+}
+
+fn world_position(model: mat4x4<f32>, vertex: Vertex) -> vec4<f32> {
+    var local = local_position(vertex);
+#ifdef SVG_STROKE
+    // The vertex is moved along the normal, away from or towards the centerline, and onto it
+    // if the stroke of the entity has a width on screen
+    let half_width = max(stroke_normal(vertex).z, 0.0) * stroke_width(vertex);
+    let offset = vertex.stroke.xy * (half_width - max(vertex.stroke.z, 0.0));
+    local += vec3<f32>(offset, 0.0);
+#endif
+    return local_to_world(model, vertex, local);
+}
+
+#ifdef SVG_STROKE
+// Moves a vertex of a stroke with a width in pixels from the centerline along the normal as
+// it is seen on screen
+fn screen_stroke(model: mat4x4<f32>, vertex: Vertex, clip: vec4<f32>) -> vec4<f32> {
+    let stroke = stroke_normal(vertex);
+    if stroke.z >= 0.0 {
+        return clip;
+    }
+    // A stroke which was given a width on screen by the entity lies off the centerline in
+    // the mesh
+    let off_centerline = vertex.stroke.xy * max(vertex.stroke.z, 0.0);
+    let local = local_position(vertex) + vec3<f32>(stroke.xy - off_centerline, 0.0);
+    let normal_clip = position_world_to_clip(local_to_world(model, vertex, local).xyz);
+    let half_viewport = view.viewport.zw * 0.5;
+    let normal = (normal_clip.xy / normal_clip.w - clip.xy / clip.w) * half_viewport;
+    let normal_length = length(normal);
+    if normal_length < 1e-6 {
+        return clip;
+    }
+    // The length of the normal is kept for the miters of the joins
+    let half_width = -stroke.z * length(stroke.xy) * stroke_width(vertex);
+    let offset = normal / normal_length * half_width / half_viewport;
+    return vec4<f32>(clip.xy + offset * clip.w, clip.zw);
+}
+#endif
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let model = mesh_functions::get_model_matrix(vertex.instance_index);
+    out.world_position = world_position(model, vertex);
+    out.position = position_world_to_clip(out.world_position.xyz);
+#ifdef SVG_STROKE
+    out.position = screen_stroke(model, vertex, out.position);
+#endif
+
+#ifdef PREPASS_PIPELINE
+#ifdef DEPTH_CLAMP_ORTHO
+    out.clip_position_unclamped = out.position;
+    out.position.z = min(out.position.z, 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    let previous_model = mesh_functions::get_previous_model_matrix(vertex.instance_index);
+    out.previous_world_position = world_position(previous_model, vertex);
+#endif
+#else
+    if is_billboard(vertex.instance_index) {
+        out.world_normal = billboard(model, local_position(vertex)).world_normal;
+    } else {
+        // The SVG lies in the xy-plane of the entity or is bent onto a surface with normals
+#ifdef VERTEX_NORMALS
+        let normal = vertex.normal;
+#else
+        let normal = vec3<f32>(0.0, 0.0, 1.0);
+#endif
+        out.world_normal = mesh_functions::mesh_normal_local_to_world(
+            normal,
+            get_instance_index(vertex.instance_index),
+        );
+    }
+#endif
+
+#ifdef VERTEX_UVS
+    out.uv = vertex.uv;
+#endif
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#ifdef SVG_COMPACT
+    out.color = unpack_color(out.color);
+#endif
+#ifdef SVG_PATH_INDEX
+    out.color = path_color(out.color, vertex.path_index);
+#endif
+#endif
+
+#ifdef SVG_PATH_INDEX
+    // All vertices of a hidden path are collapsed, so its triangles cover no pixels
+    if path_hidden(vertex.path_index) {
+        out.position = vec4<f32>(0.0);
+    }
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = get_instance_index(vertex.instance_index);
+#endif
+#ifdef BASE_INSTANCE_WORKAROUND
+    out.position.x += min(f32(get_instance_index(0u)), 0.0);
+#endif
+
+    return out;
+}
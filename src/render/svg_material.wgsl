@@ -0,0 +1,111 @@
+#define_import_path bevy_svg::svg_material
+
+// Keep in sync with `SvgMaterialUniform` and `SvgPathStylesUniform` in material.rs, and with
+// `MAX_PATH_STYLES` in svg.rs
+const MAX_PATH_STYLES: u32 = 256u;
+const SVG_PATH_STYLE_HIDDEN: u32 = 1u;
+const SVG_PATH_STYLE_COLOR: u32 = 2u;
+
+struct SvgMaterial {
+    alpha_cutoff: f32,
+    stroke_width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: SvgMaterial;
+
+#ifdef SVG_PATH_STYLES
+struct SvgPathStyle {
+    color: vec4<f32>,
+    opacity: f32,
+    stroke_width: f32,
+    flags: u32,
+};
+
+struct SvgPathStyles {
+    styles: array<SvgPathStyle, MAX_PATH_STYLES>,
+};
+
+// Only bound with all styles if the SVG has any, see `SvgMaterialKey::path_styles`
+@group(1) @binding(1)
+var<uniform> path_styles: SvgPathStyles;
+#endif
+
+// The color of a vertex of the path with the given index, with the style of the path
+fn path_color(color: vec4<f32>, index: u32) -> vec4<f32> {
+#ifdef SVG_PATH_STYLES
+    if index >= MAX_PATH_STYLES {
+        return color;
+    }
+    let style = path_styles.styles[index];
+    var out = color;
+    if (style.flags & SVG_PATH_STYLE_COLOR) != 0u {
+        out = style.color;
+    }
+    out.a *= style.opacity;
+    return out;
+#else
+    return color;
+#endif
+}
+
+fn path_hidden(index: u32) -> bool {
+#ifdef SVG_PATH_STYLES
+    return index < MAX_PATH_STYLES
+        && (path_styles.styles[index].flags & SVG_PATH_STYLE_HIDDEN) != 0u;
+#else
+    return false;
+#endif
+}
+
+// Multiplies the width of the strokes of the path with the given index
+fn path_stroke_width(index: u32) -> f32 {
+#ifdef SVG_PATH_STYLES
+    if index >= MAX_PATH_STYLES {
+        return 1.0;
+    }
+    return path_styles.styles[index].stroke_width;
+#else
+    return 1.0;
+#endif
+}
+
+// Keep in sync with the `MESH_FLAGS_*_STROKE*` constants in stroke.rs
+const SVG_MESH_FLAGS_NON_SCALING_STROKES_BIT: u32 = 33554432u;
+const SVG_MESH_FLAGS_PIXEL_STROKES_BIT: u32 = 67108864u;
+const SVG_MESH_FLAGS_SCALED_STROKES_BIT: u32 = 134217728u;
+const SVG_MESH_FLAGS_STROKE_PIXELS_SHIFT: u32 = 8u;
+
+// The stroke normal of a vertex with the `SvgStrokeWidth` and `SvgStrokeScale` of its entity,
+// which are passed in the mesh flags. A negative half width is given in pixels on screen.
+fn entity_stroke(stroke: vec4<f32>, flags: u32) -> vec4<f32> {
+    var out = stroke;
+    // The vertices of filled paths have a half width of zero
+    if stroke.z == 0.0 {
+        return out;
+    }
+    let stored = (flags >> SVG_MESH_FLAGS_STROKE_PIXELS_SHIFT) & 65535u;
+    if (flags & SVG_MESH_FLAGS_PIXEL_STROKES_BIT) != 0u {
+        // The width is stored in sixteenths of a pixel, already scaled
+        out.z = -f32(stored) / 32.0;
+        return out;
+    }
+    if (flags & SVG_MESH_FLAGS_NON_SCALING_STROKES_BIT) != 0u {
+        out.z = -abs(stroke.z);
+    }
+    if (flags & SVG_MESH_FLAGS_SCALED_STROKES_BIT) != 0u {
+        // The scale is stored in 256ths
+        out.z *= f32(stored) / 256.0;
+    }
+    return out;
+}
+
+// The linear color of a vertex of a compact mesh, whose color is stored in sRGB
+fn unpack_color(color: vec4<f32>) -> vec4<f32> {
+    let linear = select(
+        pow((color.rgb + 0.055) / 1.055, vec3<f32>(2.4)),
+        color.rgb / 12.92,
+        color.rgb <= vec3<f32>(0.04045),
+    );
+    return vec4<f32>(linear, color.a);
+}
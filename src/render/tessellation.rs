@@ -1,70 +1,493 @@
+use std::collections::HashMap;
+
 use bevy::{
     log::{debug, error},
-    math::Vec3,
+    math::{Vec2, Vec3},
     transform::components::Transform,
 };
+use copyless::VecHelper;
 use lyon_tessellation::{BuffersBuilder, FillOptions, FillTessellator, StrokeTessellator};
 
 use crate::{
-    render::vertex_buffer::{BufferExt, VertexBuffers, VertexConstructor},
-    svg::{DrawType, Svg},
+    render::vertex_buffer::{
+        BufferExt, IndexType, SvgVertexConstructor, SvgVertexContext, VertexBuffers,
+        VertexConstructor,
+    },
+    surface::SvgSurface,
+    svg::{DrawType, Svg, SvgIndexAttribute, SvgStrokeUvMode, SvgTessellation, SvgUvMode},
+    Convert,
 };
 
-pub(crate) fn generate_buffer(
+/// Tessellates all paths of `svg`, the triangles of every path are split until they are
+/// within the limits of `settings`. The vertices get the custom data of `custom`.
+pub(crate) fn generate_buffer<C: SvgVertexConstructor>(
     svg: &Svg,
     fill_tess: &mut FillTessellator,
     stroke_tess: &mut StrokeTessellator,
-) -> VertexBuffers {
+    settings: &SvgTessellation,
+    custom: &mut C,
+) -> VertexBuffers<C::Vertex> {
     debug!("Tessellating SVG: {}", svg.name);
 
     let flip_y = Transform::from_scale(Vec3::new(1.0, -1.0, 1.0));
+    let z_step = svg.render_3d.paint_order.z_step();
+    let view_box = svg.view_box;
+    let uv_offset = Vec2::new(view_box.x as f32, -view_box.y as f32);
+    // An empty viewBox must not turn the UVs into NaN, like the bounds in `fit_uvs`
+    let uv_size = Vec2::new(view_box.w as f32, view_box.h as f32).max(Vec2::splat(f32::EPSILON));
+    let uv_scale = Vec2::new(1.0, -1.0) / uv_size;
     let mut buffers = VertexBuffers::new();
 
     let mut color = None;
-    for path in &svg.paths {
+    let mut z = 0.0;
+    for (index, path) in svg.paths.iter().enumerate() {
         let mut buffer = VertexBuffers::new();
 
         if color.is_none() {
             color = Some(path.color);
         }
 
-        // Bevy has a different y-axis origin, so we need to flip that axis
-        let transform = flip_y * path.abs_transform;
+        // Bevy has a different y-axis origin, so we need to flip that axis, and with a
+        // `PaintOrder::ZOffset` later paths are moved to the front to keep them in paint order
+        let context = SvgVertexContext {
+            path,
+            index,
+            transform: Transform::from_xyz(0.0, 0.0, z) * flip_y * path.abs_transform,
+        };
+        let path_index = match settings.index {
+            SvgIndexAttribute::None => 0,
+            SvgIndexAttribute::Path => index as u32,
+            SvgIndexAttribute::Element => path.element as u32,
+        };
+        let uv_along_path = matches!(path.draw_type, DrawType::Stroke(_))
+            && settings.stroke_uv == SvgStrokeUvMode::AlongPath;
         match path.draw_type {
             DrawType::Fill => {
                 if let Err(e) = fill_tess.tessellate(
-                    path.segments.clone(),
+                    path.segments.iter().copied().map(Convert::convert),
                     &FillOptions::tolerance(0.001),
                     &mut BuffersBuilder::new(
                         &mut buffer,
                         VertexConstructor {
                             color: path.color,
-                            transform,
+                            uv_offset,
+                            uv_scale,
+                            uv_along_path,
+                            index: path_index,
+                            screen_width: false,
+                            context,
+                            custom: &mut *custom,
                         },
                     ),
                 ) {
-                    error!("FillTessellator error: {:?}", e)
+                    error!("FillTessellator error: {:?}", e);
                 }
             }
             DrawType::Stroke(opts) => {
                 if let Err(e) = stroke_tess.tessellate(
-                    path.segments.clone(),
-                    &opts,
+                    path.segments.iter().copied().map(Convert::convert),
+                    &opts.convert(),
                     &mut BuffersBuilder::new(
                         &mut buffer,
                         VertexConstructor {
                             color: path.color,
-                            transform,
+                            uv_offset,
+                            uv_scale,
+                            uv_along_path,
+                            index: path_index,
+                            screen_width: settings.stroke_normals && path.non_scaling_stroke,
+                            context,
+                            custom: &mut *custom,
                         },
                     ),
                 ) {
-                    error!("StrokeTessellator error: {:?}", e)
+                    error!("StrokeTessellator error: {:?}", e);
                 }
             }
         }
+        if settings.uv == SvgUvMode::PathBounds && !uv_along_path {
+            fit_uvs(&mut buffer);
+        }
+        if settings.subdivides() {
+            subdivide(&mut buffer, settings, custom);
+        }
         buffers.extend_one(buffer);
+        z += z_step;
     }
     debug!("Tessellating SVG: {} ... Done", svg.name);
 
     buffers
 }
+
+/// Splits the triangles of `buffer` until no edge is longer and no triangle is larger than
+/// `settings` allow. Both triangles of an edge share its midpoint, so the mesh stays without
+/// T-junctions.
+///
+/// The triangles are measured in the xy-plane of the mesh, i.e. in the user space of the SVG
+/// with the y-axis flipped, so the z-coordinates which keep the paths in paint order don't
+/// count.
+fn subdivide<C: SvgVertexConstructor>(
+    buffer: &mut VertexBuffers<C::Vertex>,
+    settings: &SvgTessellation,
+    custom: &C,
+) {
+    let max_length = settings.max_edge_length.unwrap_or(f32::INFINITY);
+    let max_area = settings.max_triangle_area.unwrap_or(f32::INFINITY);
+    if max_length.is_nan() || max_length <= 0.0 || max_area.is_nan() || max_area <= 0.0 {
+        return;
+    }
+    let max_length_squared = max_length * max_length;
+    let mut midpoints = HashMap::<(IndexType, IndexType), IndexType>::new();
+    let mut triangles: Vec<[IndexType; 3]> = buffer
+        .indices
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+
+    loop {
+        let mut split = false;
+        let mut next = Vec::with_capacity(triangles.len());
+        for [a, b, c] in triangles {
+            let [pa, pb, pc] = [a, b, c].map(|i| {
+                let [x, y, _] = buffer.vertices[i as usize].position;
+                Vec2::new(x, y)
+            });
+            let lengths = [
+                pa.distance_squared(pb),
+                pb.distance_squared(pc),
+                pc.distance_squared(pa),
+            ];
+            // A triangle that is too large is split along its longest edge
+            let longest = if (pb - pa).perp_dot(pc - pa).abs() * 0.5 > max_area {
+                (0..3).max_by(|&i, &j| lengths[i].total_cmp(&lengths[j]))
+            } else {
+                None
+            };
+
+            // An edge is split if it is too long or was split by the neighbouring triangle
+            let mut midpoint = |edge: usize, from: IndexType, to: IndexType| {
+                let key = (from.min(to), from.max(to));
+                if let Some(&index) = midpoints.get(&key) {
+                    return Some(index);
+                }
+                if lengths[edge] <= max_length_squared && longest != Some(edge) {
+                    return None;
+                }
+                let from_vertex = &buffer.vertices[from as usize];
+                let to_vertex = &buffer.vertices[to as usize];
+                let data = custom.midpoint(&from_vertex.custom, &to_vertex.custom);
+                let vertex = from_vertex.midpoint(to_vertex, data);
+                let index = buffer.vertices.len() as IndexType;
+                buffer.vertices.alloc().init(vertex);
+                midpoints.insert(key, index);
+                Some(index)
+            };
+            let edges = (midpoint(0, a, b), midpoint(1, b, c), midpoint(2, c, a));
+            split |= edges != (None, None, None);
+            match edges {
+                (None, None, None) => next.push([a, b, c]),
+                (Some(ab), None, None) => next.extend([[a, ab, c], [ab, b, c]]),
+                (None, Some(bc), None) => next.extend([[a, b, bc], [a, bc, c]]),
+                (None, None, Some(ca)) => next.extend([[a, b, ca], [ca, b, c]]),
+                (Some(ab), Some(bc), None) => {
+                    next.extend([[ab, b, bc], [a, ab, bc], [a, bc, c]]);
+                }
+                (None, Some(bc), Some(ca)) => {
+                    next.extend([[ca, bc, c], [a, b, bc], [a, bc, ca]]);
+                }
+                (Some(ab), None, Some(ca)) => {
+                    next.extend([[a, ab, ca], [ab, b, c], [ab, c, ca]]);
+                }
+                (Some(ab), Some(bc), Some(ca)) => {
+                    next.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+                }
+            }
+        }
+        triangles = next;
+        if !split {
+            break;
+        }
+    }
+
+    buffer.indices = triangles.into_iter().flatten().collect();
+}
+
+/// Moves the UVs of `buffer` from the viewBox into its bounding box.
+fn fit_uvs<T>(buffer: &mut VertexBuffers<T>) {
+    let (min, max) = buffer.vertices.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), vertex| {
+            let uv = Vec2::from(vertex.uv);
+            (min.min(uv), max.max(uv))
+        },
+    );
+    let size = (max - min).max(Vec2::splat(f32::EPSILON));
+    for vertex in &mut buffer.vertices {
+        vertex.uv = ((Vec2::from(vertex.uv) - min) / size).to_array();
+    }
+}
+
+/// Bends the vertices of `buffer` onto `surface`, which touches the flat mesh at `center`,
+/// and returns their normals. The z-coordinates of the vertices, which keep the paths in
+/// paint order, become distances along the normals.
+pub(crate) fn wrap<S: SvgSurface + ?Sized, T>(
+    buffer: &mut VertexBuffers<T>,
+    surface: &S,
+    center: Vec2,
+) -> Vec<[f32; 3]> {
+    let mut normals = Vec::with_capacity(buffer.vertices.len());
+    for vertex in &mut buffer.vertices {
+        let [x, y, z] = vertex.position;
+        let point = Vec2::new(x, y) - center;
+        let normal = surface.normal(point);
+        let position = center.extend(0.0) + surface.position(point) + normal * z;
+        vertex.position = position.to_array();
+        normals.alloc().init(normal.to_array());
+    }
+    normals
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::render::mesh::{Mesh, VertexAttributeValues};
+
+    use super::*;
+    use crate::{
+        render::vertex_buffer::Vertex,
+        surface::SvgWrap,
+        svg::{PaintOrder, ATTRIBUTE_STROKE_NORMAL},
+    };
+
+    /// A square of two triangles with the corners `0, 0` and `size, size` at `z`.
+    fn square(size: f32, z: f32) -> VertexBuffers {
+        let mut buffer = VertexBuffers::new();
+        for [x, y] in [[0.0, 0.0], [size, 0.0], [size, size], [0.0, size]] {
+            buffer.vertices.push(Vertex {
+                position: [x, y, z],
+                color: [1.0; 4],
+                uv: [x / size, y / size],
+                index: 0,
+                stroke: [0.0; 4],
+                custom: (),
+            });
+        }
+        buffer.indices = vec![0, 1, 2, 0, 2, 3];
+        buffer
+    }
+
+    fn triangles(buffer: &VertexBuffers) -> impl Iterator<Item = [Vec2; 3]> + '_ {
+        buffer.indices.chunks_exact(3).map(|t| {
+            t.iter()
+                .map(|&i| {
+                    let [x, y, _] = buffer.vertices[i as usize].position;
+                    Vec2::new(x, y)
+                })
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn subdivide_measures_in_user_space() {
+        let settings = SvgTessellation {
+            max_edge_length: Some(4.0),
+            ..Default::default()
+        };
+        // The z-coordinate of a path in paint order doesn't make its edges longer
+        let mut buffer = square(10.0, 100.0);
+        subdivide(&mut buffer, &settings, &());
+        assert!(buffer.indices.len() > 6);
+        for [a, b, c] in triangles(&buffer) {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                assert!(from.distance(to) <= 4.0, "{from} to {to} is too long");
+            }
+        }
+        assert!(buffer
+            .vertices
+            .iter()
+            .all(|vertex| vertex.position[2] == 100.0));
+
+        // Every new vertex is shared by the triangles of its edge, so none is duplicated
+        let mut positions: Vec<_> = buffer.vertices.iter().map(|v| v.position).collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        positions.dedup();
+        assert_eq!(positions.len(), buffer.vertices.len());
+    }
+
+    #[test]
+    fn subdivide_splits_large_triangles() {
+        let settings = SvgTessellation {
+            max_triangle_area: Some(10.0),
+            ..Default::default()
+        };
+        let mut buffer = square(10.0, 0.0);
+        subdivide(&mut buffer, &settings, &());
+        let areas: Vec<_> = triangles(&buffer)
+            .map(|[a, b, c]| (b - a).perp_dot(c - a) * 0.5)
+            .collect();
+        assert!(areas.iter().all(|&area| area > 0.0 && area <= 10.0));
+        assert!((areas.iter().sum::<f32>() - 100.0).abs() < 1e-3);
+
+        // Invalid limits leave the triangles alone
+        let mut buffer = square(10.0, 0.0);
+        let settings = SvgTessellation {
+            max_edge_length: Some(f32::NAN),
+            ..Default::default()
+        };
+        subdivide(&mut buffer, &settings, &());
+        assert_eq!(buffer.indices.len(), 6);
+    }
+
+    #[test]
+    fn wrap_bends_vertices_onto_surface() {
+        let mut buffer = square(FRAC_PI_2, 0.5);
+        let normals = wrap(&mut buffer, &SvgWrap::Cylinder { radius: 1.0 }, Vec2::ZERO);
+        let positions: Vec<_> = buffer.vertices.iter().map(|v| v.position).collect();
+
+        // The center touches the flat mesh, the paint order offset goes along the normal
+        assert_eq!(normals[0], [0.0, 0.0, 1.0]);
+        assert!(Vec3::from(positions[0]).abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-5));
+        // A quarter of the circumference away, the surface faces along the x-axis
+        assert!(Vec3::from(normals[1]).abs_diff_eq(Vec3::X, 1e-5));
+        assert!(Vec3::from(positions[1]).abs_diff_eq(Vec3::new(1.5, 0.0, -1.0), 1e-5));
+        assert!(Vec3::from(positions[2]).abs_diff_eq(Vec3::new(1.5, FRAC_PI_2, -1.0), 1e-5));
+    }
+
+    /// The positions and UVs of the vertices of the tessellated `svg`.
+    fn positions_and_uvs(svg: &Svg) -> Vec<(Vec2, Vec2)> {
+        let mesh = svg.tessellate();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("the mesh has no positions");
+        };
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("the mesh has no UVs");
+        };
+        positions
+            .iter()
+            .zip(uvs)
+            .map(|(&[x, y, _], &uv)| (Vec2::new(x, y), Vec2::from(uv)))
+            .collect()
+    }
+
+    fn uv_bounds(vertices: &[(Vec2, Vec2)]) -> (Vec2, Vec2) {
+        vertices.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), (_, uv)| (min.min(*uv), max.max(*uv)),
+        )
+    }
+
+    #[test]
+    fn fill_uvs_span_view_box_or_path_bounds() {
+        // A rect in the top left quarter of the viewBox
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="10 20 100 50">
+                <rect x="10" y="20" width="50" height="25"/>
+            </svg>"#,
+            "quarter.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let vertices = positions_and_uvs(&svg);
+        for (position, uv) in &vertices {
+            let expected = Vec2::new((position.x - 10.0) / 100.0, (-position.y - 20.0) / 50.0);
+            assert!(uv.abs_diff_eq(expected, 1e-5), "{uv} is not {expected}");
+        }
+        let (min, max) = uv_bounds(&vertices);
+        assert!(min.abs_diff_eq(Vec2::ZERO, 1e-5));
+        assert!(max.abs_diff_eq(Vec2::splat(0.5), 1e-5));
+
+        svg.tessellation.uv = SvgUvMode::PathBounds;
+        let (min, max) = uv_bounds(&positions_and_uvs(&svg));
+        assert!(min.abs_diff_eq(Vec2::ZERO, 1e-5));
+        assert!(max.abs_diff_eq(Vec2::ONE, 1e-5));
+    }
+
+    #[test]
+    fn stroke_uvs_go_along_and_across_the_path() {
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 10">
+                <path d="M 5 5 H 15" stroke="black" stroke-width="2" fill="none"/>
+            </svg>"#,
+            "line.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let vertices = positions_and_uvs(&svg);
+        let (min, max) = uv_bounds(&vertices);
+        assert!(min.abs_diff_eq(Vec2::ZERO, 1e-5));
+        assert!(max.abs_diff_eq(Vec2::new(10.0, 1.0), 1e-5));
+        for (position, uv) in &vertices {
+            // The distance along the path, and the left side of the path going right is at
+            // the top
+            assert!((uv.x - (position.x - 5.0)).abs() < 1e-5);
+            let top = position.y > -5.0;
+            assert_eq!(uv.y, if top { 0.0 } else { 1.0 });
+        }
+
+        // The strokes can also get the UVs of the fills
+        svg.tessellation.stroke_uv = SvgStrokeUvMode::Fill;
+        let (min, max) = uv_bounds(&positions_and_uvs(&svg));
+        assert!(min.abs_diff_eq(Vec2::new(0.25, 0.4), 1e-5));
+        assert!(max.abs_diff_eq(Vec2::new(0.75, 0.6), 1e-5));
+    }
+
+    #[test]
+    fn stroke_vertices_carry_centerline_normal_and_side() {
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 10">
+                <path d="M 5 5 H 15" stroke="black" stroke-width="2" fill="none"/>
+            </svg>"#,
+            "line.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        svg.tessellation.stroke_normals = true;
+        let mesh = svg.tessellate();
+        let Some(VertexAttributeValues::Float32x4(strokes)) =
+            mesh.attribute(ATTRIBUTE_STROKE_NORMAL)
+        else {
+            panic!("the mesh has no stroke normals");
+        };
+        let vertices = positions_and_uvs(&svg);
+        assert_eq!(strokes.len(), vertices.len());
+        for ((position, _), &[x, y, half_width, side]) in vertices.iter().zip(strokes) {
+            let normal = Vec2::new(x, y);
+            assert!(normal.abs_diff_eq(Vec2::Y, 1e-5) || normal.abs_diff_eq(Vec2::NEG_Y, 1e-5));
+            assert_eq!(half_width, 1.0);
+            // The vertex is the centerline moved along the normal by the half width
+            let centerline = *position - normal * half_width;
+            assert!((centerline.y + 5.0).abs() < 1e-5);
+            assert!((5.0..=15.0).contains(&centerline.x));
+            // The left side of the path going right is at the top
+            let top = position.y > -5.0;
+            assert_eq!(side, if top { -1.0 } else { 1.0 });
+        }
+    }
+
+    #[test]
+    fn empty_view_box_keeps_uvs_finite() {
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <rect width="10" height="10"/>
+            </svg>"#,
+            "empty.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        svg.view_box.w = 0.0;
+        svg.view_box.h = 0.0;
+        svg.render_3d.paint_order = PaintOrder::NoDepthWrite;
+        let mesh = svg.tessellate();
+        let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        else {
+            panic!("the mesh has no UVs");
+        };
+        assert!(uvs.iter().flatten().all(|uv| uv.is_finite()));
+    }
+}
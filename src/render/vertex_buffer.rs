@@ -1,80 +1,258 @@
 use bevy::{
-    math::Vec3,
+    math::{Vec2, Vec3},
     render::{
         color::Color,
-        mesh::{Indices, Mesh},
+        mesh::{Indices, Mesh, VertexAttributeValues},
         render_resource::PrimitiveTopology,
     },
     transform::components::Transform,
 };
 use copyless::VecHelper;
 use lyon_tessellation::{
-    self, FillVertex, FillVertexConstructor, StrokeVertex, StrokeVertexConstructor,
+    self, math::Point, FillVertex, FillVertexConstructor, Side, StrokeVertex,
+    StrokeVertexConstructor,
 };
 
-use crate::Convert;
+use crate::{
+    svg::{PathDescriptor, ATTRIBUTE_COLOR_PACKED, ATTRIBUTE_POSITION_2D},
+    Convert,
+};
+
+/// Builds custom vertex attributes for the mesh of an [`Svg`](crate::svg::Svg), see
+/// [`Svg::tessellate_with`](crate::svg::Svg::tessellate_with).
+///
+/// The constructor is called for every vertex that Lyon creates while tessellating the
+/// fills and strokes, next to the built-in constructor which creates the positions, colors,
+/// UVs and path indices. Its vertices are collected in the order of the mesh and handed to
+/// [`SvgVertexConstructor::insert_attributes`], which adds them to the mesh, e.g. as
+/// attributes with the stroke normal, the distance along the path or any custom data for a
+/// shader. The unit type `()` is the constructor without custom attributes.
+pub trait SvgVertexConstructor {
+    /// The custom data of a vertex.
+    type Vertex: Clone;
+
+    /// The data of a vertex of a filled path.
+    fn fill_vertex(&mut self, path: &SvgVertexContext, vertex: FillVertex) -> Self::Vertex;
+
+    /// The data of a vertex of a stroked path.
+    fn stroke_vertex(&mut self, path: &SvgVertexContext, vertex: StrokeVertex) -> Self::Vertex;
+
+    /// The data of the vertex halfway between `a` and `b`, for the triangles that are split
+    /// by the limits of the [`SvgTessellation`](crate::svg::SvgTessellation). Returns a copy
+    /// of `a` by default.
+    fn midpoint(&self, a: &Self::Vertex, _b: &Self::Vertex) -> Self::Vertex {
+        a.clone()
+    }
+
+    /// Inserts the custom attributes of all `vertices` of the mesh, in the order of its
+    /// positions.
+    fn insert_attributes(&self, mesh: &mut Mesh, vertices: Vec<Self::Vertex>);
+}
+
+impl SvgVertexConstructor for () {
+    type Vertex = ();
+
+    fn fill_vertex(&mut self, _path: &SvgVertexContext, _vertex: FillVertex) {}
+
+    fn stroke_vertex(&mut self, _path: &SvgVertexContext, _vertex: StrokeVertex) {}
+
+    fn insert_attributes(&self, _mesh: &mut Mesh, _vertices: Vec<()>) {}
+}
+
+/// The path whose vertices are passed to a [`SvgVertexConstructor`].
+#[derive(Clone, Copy, Debug)]
+pub struct SvgVertexContext<'a> {
+    /// The path being tessellated.
+    pub path: &'a PathDescriptor,
+    /// The index of the path in [`Svg::paths`](crate::svg::Svg::paths).
+    pub index: usize,
+    /// Transforms the positions of the Lyon vertices into the mesh, which flips the y-axis
+    /// and keeps the paths in paint order in 3D.
+    pub transform: Transform,
+}
+
+impl SvgVertexContext<'_> {
+    /// The position of a Lyon vertex in the mesh, before it is bent onto a
+    /// [`SvgWrap`](crate::surface::SvgWrap).
+    pub fn position(&self, point: Point) -> Vec3 {
+        self.transform * Vec3::new(point.x, point.y, 0.0)
+    }
+}
 
 /// A vertex with all the necessary attributes to be inserted into a Bevy
-/// [`Mesh`](bevy::render::mesh::Mesh).
+/// [`Mesh`](bevy::render::mesh::Mesh), and the custom data of a [`SvgVertexConstructor`].
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Vertex {
-    position: [f32; 3],
-    color: [f32; 4],
+pub(crate) struct Vertex<T = ()> {
+    pub(crate) position: [f32; 3],
+    pub(crate) color: [f32; 4],
+    pub(crate) uv: [f32; 2],
+    pub(crate) index: u32,
+    /// See [`ATTRIBUTE_STROKE_NORMAL`](crate::svg::ATTRIBUTE_STROKE_NORMAL).
+    pub(crate) stroke: [f32; 4],
+    pub(crate) custom: T,
+}
+
+impl<T> Vertex<T> {
+    /// The vertex halfway between `self` and `other`, with the given custom data.
+    pub(crate) fn midpoint(&self, other: &Self, custom: T) -> Self {
+        Self {
+            position: std::array::from_fn(|i| (self.position[i] + other.position[i]) * 0.5),
+            color: std::array::from_fn(|i| (self.color[i] + other.color[i]) * 0.5),
+            uv: std::array::from_fn(|i| (self.uv[i] + other.uv[i]) * 0.5),
+            index: self.index,
+            stroke: std::array::from_fn(|i| (self.stroke[i] + other.stroke[i]) * 0.5),
+            custom,
+        }
+    }
 }
 
 /// The index type of a Bevy [`Mesh`](bevy::render::mesh::Mesh).
 pub(crate) type IndexType = u32;
 
 /// Lyon's [`VertexBuffers`] generic data type defined for [`Vertex`].
-pub(crate) type VertexBuffers = lyon_tessellation::VertexBuffers<Vertex, IndexType>;
+pub(crate) type VertexBuffers<T = ()> = lyon_tessellation::VertexBuffers<Vertex<T>, IndexType>;
 
-impl Convert<Mesh> for VertexBuffers {
-    fn convert(self) -> Mesh {
+impl<T> Convert<(Mesh, Vec<T>)> for VertexBuffers<T> {
+    /// Converts the built-in attributes into a mesh, the custom data is returned separately.
+    fn convert(self) -> (Mesh, Vec<T>) {
         let mut positions = Vec::with_capacity(self.vertices.len());
         let mut colors = Vec::with_capacity(self.vertices.len());
+        let mut uvs = Vec::with_capacity(self.vertices.len());
+        let mut custom = Vec::with_capacity(self.vertices.len());
 
-        for vert in self.vertices.into_iter() {
+        for vert in self.vertices {
             positions.alloc().init(vert.position);
             colors.alloc().init(vert.color);
+            uvs.alloc().init(vert.uv);
+            custom.alloc().init(vert.custom);
         }
 
         let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
         mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
         mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
         mesh.set_indices(Some(Indices::U32(self.indices)));
 
-        mesh
+        (mesh, custom)
     }
 }
 
-/// Zero-sized type used to implement various vertex construction traits from Lyon.
-pub(crate) struct VertexConstructor {
+/// Converts the buffers into a mesh with the
+/// [`SvgVertexFormat::Compact`](crate::svg::SvgVertexFormat::Compact), the custom data is
+/// returned separately.
+pub(crate) fn into_compact_mesh<T>(buffers: VertexBuffers<T>) -> (Mesh, Vec<T>) {
+    let mut positions = Vec::with_capacity(buffers.vertices.len());
+    let mut colors = Vec::with_capacity(buffers.vertices.len());
+    let mut custom = Vec::with_capacity(buffers.vertices.len());
+
+    for vert in buffers.vertices {
+        let [x, y, _] = vert.position;
+        let [red, green, blue, alpha] = vert.color;
+        positions.alloc().init([x, y]);
+        // Rounded, so the 8-bit colors of the SVG come out unchanged, and clamped to the
+        // range of a `u8`, because custom colors can be out of range
+        let srgb = Color::rgba_linear(red, green, blue, alpha).as_rgba_f32();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let packed = srgb.map(|channel| (channel * 255.0).round().clamp(0.0, 255.0) as u8);
+        colors.alloc().init(packed);
+        custom.alloc().init(vert.custom);
+    }
+
+    let indices = if positions.len() <= 1 << 16 {
+        Indices::U16(buffers.indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(buffers.indices)
+    };
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(ATTRIBUTE_POSITION_2D, positions);
+    mesh.insert_attribute(
+        ATTRIBUTE_COLOR_PACKED,
+        VertexAttributeValues::Unorm8x4(colors),
+    );
+    mesh.set_indices(Some(indices));
+
+    (mesh, custom)
+}
+
+/// Implements the vertex construction traits from Lyon, creates the built-in attributes and
+/// passes the vertices on to the [`SvgVertexConstructor`].
+pub(crate) struct VertexConstructor<'a, C> {
     pub(crate) color: Color,
-    pub(crate) transform: Transform,
+    /// The top left corner of the viewBox in the mesh.
+    pub(crate) uv_offset: Vec2,
+    /// Scales the position of a vertex in the viewBox to its UV.
+    pub(crate) uv_scale: Vec2,
+    /// Whether the UVs of strokes follow the path instead of the viewBox.
+    pub(crate) uv_along_path: bool,
+    /// The index of the path or element, see [`SvgIndexAttribute`](crate::svg::SvgIndexAttribute).
+    pub(crate) index: u32,
+    /// Whether the stroke has a width in pixels on screen, its vertices are then placed on
+    /// the centerline and widened in the vertex shader.
+    pub(crate) screen_width: bool,
+    /// The path being tessellated.
+    pub(crate) context: SvgVertexContext<'a>,
+    /// Creates the custom data of the vertices.
+    pub(crate) custom: &'a mut C,
 }
 
 /// Enables the construction of a [`Vertex`] when using a `FillTessellator`.
-impl FillVertexConstructor<Vertex> for VertexConstructor {
-    fn new_vertex(&mut self, vertex: FillVertex) -> Vertex {
-        let vertex = vertex.position();
-        let pos = self.transform * Vec3::new(vertex.x, vertex.y, 0.0);
+impl<C: SvgVertexConstructor> FillVertexConstructor<Vertex<C::Vertex>>
+    for VertexConstructor<'_, C>
+{
+    fn new_vertex(&mut self, vertex: FillVertex) -> Vertex<C::Vertex> {
+        let pos = self.context.position(vertex.position());
 
         Vertex {
             position: [pos.x, pos.y, pos.z],
             color: self.color.as_linear_rgba_f32(),
+            index: self.index,
+            uv: ((pos.truncate() - self.uv_offset) * self.uv_scale).to_array(),
+            stroke: [0.0; 4],
+            custom: self.custom.fill_vertex(&self.context, vertex),
         }
     }
 }
 
 /// Enables the construction of a [`Vertex`] when using a `StrokeTessellator`.
-impl StrokeVertexConstructor<Vertex> for VertexConstructor {
-    fn new_vertex(&mut self, vertex: StrokeVertex) -> Vertex {
-        let vertex = vertex.position();
-        let pos = self.transform * Vec3::new(vertex.x, vertex.y, 0.0);
+impl<C: SvgVertexConstructor> StrokeVertexConstructor<Vertex<C::Vertex>>
+    for VertexConstructor<'_, C>
+{
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> Vertex<C::Vertex> {
+        let advancement = vertex.advancement();
+        let side = match vertex.side() {
+            Side::Negative => 0.0,
+            Side::Positive => 1.0,
+        };
+        // Distances along and across the path are scaled from the units of the path to the mesh
+        let transform = self.context.transform;
+        let scale = (transform.scale.x * transform.scale.y).abs().sqrt();
+        let normal = vertex.normal();
+        let normal = (transform.rotation * (transform.scale * Vec3::new(normal.x, normal.y, 0.0)))
+            .truncate()
+            / scale;
+        // The width of a non-scaling stroke is given in pixels, as a negative half width
+        let (pos, half_width) = if self.screen_width {
+            let pos = self.context.position(vertex.position_on_path());
+            (pos, -vertex.line_width() * 0.5)
+        } else {
+            let pos = self.context.position(vertex.position());
+            (pos, vertex.line_width() * 0.5 * scale)
+        };
+        let stroke = [normal.x, normal.y, half_width, side * 2.0 - 1.0];
+        let uv = if self.uv_along_path {
+            Vec2::new(advancement * scale, side)
+        } else {
+            (pos.truncate() - self.uv_offset) * self.uv_scale
+        };
 
         Vertex {
             position: [pos.x, pos.y, pos.z],
             color: self.color.as_linear_rgba_f32(),
+            index: self.index,
+            uv: uv.to_array(),
+            stroke,
+            custom: self.custom.stroke_vertex(&self.context, vertex),
         }
     }
 }
@@ -84,30 +262,151 @@ pub(crate) trait BufferExt<A> {
     fn extend<T: IntoIterator<Item = A>>(&mut self, iter: T);
 }
 
-impl BufferExt<VertexBuffers> for VertexBuffers {
-    fn extend_one(&mut self, item: VertexBuffers) {
+impl<T> BufferExt<Self> for VertexBuffers<T> {
+    fn extend_one(&mut self, item: Self) {
         let offset = self.vertices.len() as u32;
 
-        for vert in item.vertices.into_iter() {
+        for vert in item.vertices {
             self.vertices.alloc().init(vert);
         }
-        for idx in item.indices.into_iter() {
+        for idx in item.indices {
             self.indices.alloc().init(idx + offset);
         }
     }
 
-    fn extend<T: IntoIterator<Item = VertexBuffers>>(&mut self, iter: T) {
+    fn extend<I: IntoIterator<Item = Self>>(&mut self, iter: I) {
         let mut offset = self.vertices.len() as u32;
 
-        for buf in iter.into_iter() {
+        for buf in iter {
             let num_verts = buf.vertices.len() as u32;
-            for vert in buf.vertices.into_iter() {
+            for vert in buf.vertices {
                 self.vertices.alloc().init(vert);
             }
-            for idx in buf.indices.into_iter() {
+            for idx in buf.indices {
                 self.indices.alloc().init(idx + offset);
             }
             offset += num_verts;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::{mesh::MeshVertexAttribute, render_resource::VertexFormat};
+
+    use super::*;
+    use crate::svg::Svg;
+
+    /// Buffers with `count` vertices and a triangle whose last index is the last vertex.
+    fn buffers(count: usize) -> VertexBuffers {
+        let vertex = Vertex {
+            position: [1.0, 2.0, 3.0],
+            color: Color::rgb_u8(200, 100, 50).as_linear_rgba_f32(),
+            uv: [0.0; 2],
+            index: 0,
+            stroke: [0.0; 4],
+            custom: (),
+        };
+        let mut buffers = VertexBuffers::new();
+        buffers.vertices = vec![vertex; count];
+        buffers.indices = vec![0, 1, count as u32 - 1];
+        buffers
+    }
+
+    #[test]
+    fn compact_mesh_uses_u16_indices_if_they_fit() {
+        let (mesh, _) = into_compact_mesh(buffers(1 << 16));
+        let Some(Indices::U16(indices)) = mesh.indices() else {
+            panic!("expected u16 indices");
+        };
+        assert_eq!(indices, &[0, 1, u16::MAX]);
+
+        let (mesh, _) = into_compact_mesh(buffers((1 << 16) + 1));
+        let Some(Indices::U32(indices)) = mesh.indices() else {
+            panic!("expected u32 indices");
+        };
+        assert_eq!(indices, &[0, 1, 1 << 16]);
+    }
+
+    #[test]
+    fn compact_mesh_keeps_8_bit_colors() {
+        let (mesh, _) = into_compact_mesh(buffers(3));
+        let Some(VertexAttributeValues::Float32x2(positions)) =
+            mesh.attribute(ATTRIBUTE_POSITION_2D)
+        else {
+            panic!("expected 2D positions");
+        };
+        assert_eq!(positions, &[[1.0, 2.0]; 3]);
+        let Some(VertexAttributeValues::Unorm8x4(colors)) = mesh.attribute(ATTRIBUTE_COLOR_PACKED)
+        else {
+            panic!("expected packed colors");
+        };
+        assert_eq!(colors, &[[200, 100, 50, 255]; 3]);
+
+        // Colors out of range are clamped
+        let mut out_of_range = buffers(3);
+        out_of_range.vertices[0].color = [2.0, -1.0, 0.0, 1.5];
+        let (mesh, _) = into_compact_mesh(out_of_range);
+        let Some(VertexAttributeValues::Unorm8x4(colors)) = mesh.attribute(ATTRIBUTE_COLOR_PACKED)
+        else {
+            panic!("expected packed colors");
+        };
+        assert_eq!(colors[0], [255, 0, 0, 255]);
+    }
+
+    const ATTRIBUTE_PATH_KIND: MeshVertexAttribute =
+        MeshVertexAttribute::new("Vertex_PathKind", 988_540_917, VertexFormat::Float32x2);
+
+    /// Writes the index of the path and whether the vertex is on a stroke.
+    struct PathKind;
+
+    impl SvgVertexConstructor for PathKind {
+        type Vertex = [f32; 2];
+
+        fn fill_vertex(&mut self, path: &SvgVertexContext, _vertex: FillVertex) -> [f32; 2] {
+            [path.index as f32, 0.0]
+        }
+
+        fn stroke_vertex(&mut self, path: &SvgVertexContext, _vertex: StrokeVertex) -> [f32; 2] {
+            [path.index as f32, 1.0]
+        }
+
+        fn insert_attributes(&self, mesh: &mut Mesh, vertices: Vec<[f32; 2]>) {
+            mesh.insert_attribute(ATTRIBUTE_PATH_KIND, vertices);
+        }
+    }
+
+    #[test]
+    fn custom_constructor_adds_attributes() {
+        let svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <rect width="5" height="5"/>
+                <path d="M 0 8 H 10" stroke="black" fill="none"/>
+            </svg>"#,
+            "kinds.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let mesh = svg.tessellate_with(&mut PathKind);
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("expected positions");
+        };
+        let Some(VertexAttributeValues::Float32x2(kinds)) = mesh.attribute(ATTRIBUTE_PATH_KIND)
+        else {
+            panic!("expected the custom attribute");
+        };
+        assert_eq!(kinds.len(), positions.len());
+        // The vertices of the stroke are below the rect, with the y-axis flipped
+        for (position, kind) in positions.iter().zip(kinds) {
+            let expected = if position[1] < -6.0 {
+                [1.0, 1.0]
+            } else {
+                [0.0, 0.0]
+            };
+            assert_eq!(*kind, expected);
+        }
+        assert!(kinds.contains(&[0.0, 0.0]) && kinds.contains(&[1.0, 1.0]));
+    }
+}
@@ -0,0 +1,391 @@
+use bevy::{
+    asset::{AssetPath, AssetServer, Handle, LoadContext, LoadState},
+    core::Name,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::{Changed, Or, With, Without},
+        reflect::ReflectComponent,
+        system::{Commands, Query, Res},
+        world::World,
+    },
+    hierarchy::BuildWorldChildren,
+    math::{Mat4, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::prelude::SpatialBundle,
+    scene::Scene,
+    transform::components::Transform,
+};
+
+#[cfg(feature = "2d")]
+use crate::render::Svg2d;
+#[cfg(feature = "3d")]
+use crate::render::Svg3d;
+use crate::{
+    loader::{HierarchyScene, SvgLoadedSettings, SvgLoaderSettings},
+    status::SvgEvent,
+    svg::Svg,
+};
+
+/// Minimal distance along the z-axis between the paths of a hierarchy scene, which keeps
+/// them in paint order.
+const ELEMENT_Z_STEP: f32 = 0.001;
+
+#[derive(Clone, Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// Asset path of the [`Svg`] of an entity, e.g. `"images/map.svg"`, and the
+/// [`SvgLoaderSettings`] it was loaded with.
+///
+/// A [`Handle<Svg>`] can not be saved in a scene, so this component is added to every SVG
+/// entity whose [`Svg`] was loaded from a file, and updated or removed when the
+/// [`Handle<Svg>`] of the entity changes. When an entity is spawned from a scene, the
+/// [`Svg`] is loaded from this path with these settings and its [`Handle<Svg>`] is added to
+/// the entity, so labeled assets which depend on the settings, e.g.
+/// `"map.svg#Scene/Svg/<element>"`, are loaded as well. Changing the path loads the new
+/// [`Svg`].
+///
+/// When saving a scene, deny the [`Handle<Svg>`] and mesh handle components, they are
+/// restored by the plugin.
+pub struct SvgPath {
+    /// The asset path, including the label of a labeled [`Svg`].
+    pub path: String,
+    /// The settings of the asset loader for the file of the path.
+    pub settings: SvgLoaderSettings,
+}
+
+impl SvgPath {
+    /// The asset path of an [`Svg`] which is loaded with the default settings.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            settings: SvgLoaderSettings::default(),
+        }
+    }
+}
+
+/// Keeps the handle of the file of a labeled [`SvgPath`], until its labeled [`Svg`] is loaded.
+#[derive(Component)]
+pub(crate) struct SvgFileLoading {
+    _file: Handle<Svg>,
+}
+
+/// Entities with a new or changed [`SvgPath`], or whose [`Svg`] was not loaded yet.
+type ChangedSvgPath = Or<(Changed<SvgPath>, Without<Handle<Svg>>)>;
+
+/// Loads the [`Svg`] of every entity with a new or changed [`SvgPath`].
+///
+/// Bevy loads the file of a labeled asset path with the settings of the handle of the file,
+/// so the file is loaded with the settings of the [`SvgPath`] first and its handle is kept
+/// until the labeled [`Svg`] is loaded.
+pub(crate) fn load_svg_paths(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query: Query<(Entity, &SvgPath, Option<&Handle<Svg>>), ChangedSvgPath>,
+    loading: Query<(Entity, &Handle<Svg>), With<SvgFileLoading>>,
+) {
+    for (entity, handle) in &loading {
+        if matches!(
+            asset_server.load_state(handle),
+            LoadState::Loaded | LoadState::Failed
+        ) {
+            commands.entity(entity).remove::<SvgFileLoading>();
+        }
+    }
+
+    for (entity, SvgPath { path, settings }, handle) in &query {
+        let is_current = handle
+            .and_then(Handle::path)
+            .is_some_and(|current| current.to_string() == *path);
+        if is_current {
+            continue;
+        }
+
+        let path = AssetPath::parse(path);
+        let settings = settings.clone();
+        let file = asset_server.load_with_settings::<Svg, SvgLoaderSettings>(
+            path.without_label().into_owned(),
+            move |s| *s = settings.clone(),
+        );
+        if path.label().is_none() {
+            commands
+                .entity(entity)
+                .insert(file)
+                .remove::<SvgFileLoading>();
+        } else {
+            commands.entity(entity).insert((
+                asset_server.load::<Svg>(path),
+                SvgFileLoading { _file: file },
+            ));
+        }
+    }
+}
+
+/// Keeps the [`SvgPath`] of every entity whose [`Svg`] became ready in sync with its
+/// [`Handle<Svg>`]. The path and the settings its file was loaded with are added or updated
+/// if the [`Svg`] was loaded from a file and removed otherwise.
+pub(crate) fn record_svg_paths(
+    mut commands: Commands,
+    loaded_settings: Res<SvgLoadedSettings>,
+    mut svg_events: EventReader<SvgEvent>,
+    query: Query<(&Handle<Svg>, Option<&SvgPath>)>,
+) {
+    for event in svg_events.read() {
+        let SvgEvent::Ready { entity, handle } = event else {
+            continue;
+        };
+        // Skip events of handles which were swapped again since
+        let Ok((current, svg_path)) = query.get(*entity) else {
+            continue;
+        };
+        if current != handle {
+            continue;
+        }
+        match handle.path() {
+            Some(path) => {
+                let path = SvgPath {
+                    path: path.to_string(),
+                    settings: loaded_settings.get(path.path()).unwrap_or_default(),
+                };
+                if svg_path != Some(&path) {
+                    commands.entity(*entity).insert(path);
+                }
+            }
+            None if svg_path.is_some() => {
+                commands.entity(*entity).remove::<SvgPath>();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Creates the [`Scene`] of [`HierarchyScene`] for `svg`. The [`Svg`] and mesh of each path
+/// element are added as labeled assets `"Scene/Svg/<element>"` and `"Scene/mesh/<element>"`.
+pub(crate) fn hierarchy_scene(
+    svg: &Svg,
+    kind: HierarchyScene,
+    load_context: &mut LoadContext,
+) -> Scene {
+    // Bevy has a different y-axis origin, so transforms between SVG user spaces are flipped
+    let flip_y = Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0));
+    let relative = |parent: Option<&Transform>, child: &Transform| {
+        let parent = parent.map_or(Mat4::IDENTITY, Transform::compute_matrix);
+        parent.inverse() * child.compute_matrix()
+    };
+
+    let mut world = World::new();
+    let root = world
+        .spawn((Name::new(svg.name.clone()), SpatialBundle::default()))
+        .id();
+
+    let mut groups = Vec::with_capacity(svg.groups.len());
+    for (index, group) in svg.groups.iter().enumerate() {
+        let parent = group.parent.map(|p| &svg.groups[p].abs_transform);
+        let transform =
+            Transform::from_matrix(flip_y * relative(parent, &group.abs_transform) * flip_y);
+        let name = if group.id.is_empty() {
+            format!("group{index}")
+        } else {
+            group.id.clone()
+        };
+        // Groups are in document order, so the parent has already been spawned
+        let parent = group.parent.map_or(root, |p| groups[p]);
+        let entity = world
+            .spawn((Name::new(name), SpatialBundle::from_transform(transform)))
+            .set_parent(parent)
+            .id();
+        groups.push(entity);
+    }
+
+    // Each element starts where its paths would be in the mesh of the whole SVG. 2D meshes
+    // have no paint order settings, their entities are only sorted by z.
+    let z_step = match kind {
+        HierarchyScene::Svg3d => svg.render_3d.paint_order.z_step().max(ELEMENT_Z_STEP),
+        HierarchyScene::Svg2d | HierarchyScene::None => ELEMENT_Z_STEP,
+    };
+    let mut z = 0.0;
+    let mut start = 0;
+    while start < svg.paths.len() {
+        let element = svg.paths[start].element;
+        let end = svg.paths[start..]
+            .iter()
+            .position(|path| path.element != element)
+            .map_or(svg.paths.len(), |len| start + len);
+        let group = svg.paths[start].group;
+        let group_transform = group.map(|g| &svg.groups[g].abs_transform);
+
+        let paths = svg.paths[start..end]
+            .iter()
+            .map(|path| {
+                let mut path = path.clone();
+                path.abs_transform =
+                    Transform::from_matrix(relative(group_transform, &path.abs_transform));
+                path.group = None;
+                path
+            })
+            .collect();
+        let mut element_svg = Svg {
+            groups: Vec::new(),
+            ..svg.with_paths(format!("{}#{element}", svg.name), paths)
+        };
+        element_svg.mesh = load_context
+            .add_labeled_asset(format!("Scene/mesh/{element}"), element_svg.tessellate());
+        let handle = load_context.add_labeled_asset(format!("Scene/Svg/{element}"), element_svg);
+
+        let transform = Transform::from_xyz(0.0, 0.0, z);
+        let mut entity = world.spawn((
+            Name::new(if svg.paths[start].id.is_empty() {
+                format!("path{element}")
+            } else {
+                svg.paths[start].id.clone()
+            }),
+            SpatialBundle::from_transform(transform),
+            handle,
+        ));
+        match kind {
+            #[cfg(feature = "2d")]
+            HierarchyScene::Svg2d => {
+                entity.insert(Svg2d);
+            }
+            #[cfg(feature = "3d")]
+            HierarchyScene::Svg3d => {
+                entity.insert(Svg3d);
+            }
+            _ => {}
+        }
+        entity.set_parent(group.map_or(root, |g| groups[g]));
+
+        z = svg.paths[start..end].iter().fold(z, |z, _| z + z_step);
+        start = end;
+    }
+
+    Scene::new(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        asset::Assets,
+        ecs::{reflect::AppTypeRegistry, schedule::IntoSystemConfigs, world::Mut},
+        scene::{DynamicScene, DynamicSceneBuilder},
+        utils::HashMap,
+    };
+
+    use super::*;
+    use crate::loader::tests::{load, wait_for, AssetDir};
+
+    const MAP: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <g id="land"><rect id="island" width="5" height="5"/></g>
+    </svg>"#;
+
+    fn app(files: &[(&str, &str)]) -> App {
+        let mut app = crate::loader::tests::app(files);
+        app.add_event::<SvgEvent>()
+            .register_type::<SvgPath>()
+            .add_systems(Update, (load_svg_paths, record_svg_paths).chain());
+        app
+    }
+
+    fn swap_handle(app: &mut App, entity: Entity, handle: Handle<Svg>) {
+        app.world.entity_mut(entity).insert(handle.clone());
+        app.world.send_event(SvgEvent::Ready { entity, handle });
+        app.update();
+    }
+
+    fn svg_path(app: &App, entity: Entity) -> Option<&str> {
+        app.world
+            .get::<SvgPath>(entity)
+            .map(|svg_path| svg_path.path.as_str())
+    }
+
+    #[test]
+    fn records_swapped_handles() {
+        let mut app = app(&[]);
+        let entity = app.world.spawn_empty().id();
+
+        let handle = app.world.resource::<AssetServer>().load("a.svg");
+        swap_handle(&mut app, entity, handle);
+        assert_eq!(svg_path(&app, entity), Some("a.svg"));
+
+        let handle = app.world.resource::<AssetServer>().load("b.svg");
+        swap_handle(&mut app, entity, handle);
+        assert_eq!(svg_path(&app, entity), Some("b.svg"));
+
+        let handle = app.world.resource_mut::<Assets<Svg>>().add(Svg::default());
+        swap_handle(&mut app, entity, handle);
+        assert_eq!(svg_path(&app, entity), None);
+    }
+
+    #[test]
+    fn ignores_outdated_events() {
+        let mut app = app(&[]);
+        let a = app.world.resource::<AssetServer>().load::<Svg>("a.svg");
+        let b = app.world.resource::<AssetServer>().load::<Svg>("b.svg");
+        let entity = app.world.spawn(b).id();
+        app.world.send_event(SvgEvent::Ready { entity, handle: a });
+        app.update();
+        assert_eq!(svg_path(&app, entity), None);
+    }
+
+    #[test]
+    fn scene_round_trip() {
+        // The element `Svg`s of a hierarchy scene only exist with these settings
+        let settings = SvgLoaderSettings {
+            scene: HierarchyScene::Svg2d,
+            ..Default::default()
+        };
+        let path = "map.svg#Scene/Svg/0";
+        let mut app = app(&[("map.svg", MAP)]);
+        let _file = load(&mut app, "map.svg", settings.clone());
+        let handle = app.world.resource::<AssetServer>().load(path);
+        wait_for(&mut app, &handle);
+        let entity = app.world.spawn_empty().id();
+        swap_handle(&mut app, entity, handle);
+        assert_eq!(
+            app.world.get::<SvgPath>(entity),
+            Some(&SvgPath {
+                path: path.to_string(),
+                settings: settings.clone(),
+            })
+        );
+
+        let saved = DynamicSceneBuilder::from_world(&app.world)
+            .deny::<Handle<Svg>>()
+            .extract_entity(entity)
+            .build()
+            .serialize_ron(&app.world.resource::<AppTypeRegistry>().0)
+            .unwrap();
+        std::fs::write(
+            app.world.resource::<AssetDir>().0.join("saved.scn.ron"),
+            &saved,
+        )
+        .unwrap();
+
+        // A new app, in which the SVG isn't loaded yet
+        let mut app = self::app(&[("map.svg", MAP), ("saved.scn.ron", &saved)]);
+        let scene = app
+            .world
+            .resource::<AssetServer>()
+            .load::<DynamicScene>("saved.scn.ron");
+        wait_for(&mut app, &scene);
+        let mut entities = HashMap::default();
+        app.world
+            .resource_scope(|world, scenes: Mut<Assets<DynamicScene>>| {
+                scenes
+                    .get(&scene)
+                    .unwrap()
+                    .write_to_world(world, &mut entities)
+            })
+            .unwrap();
+        let entity = entities[&entity];
+        assert_eq!(app.world.get::<SvgPath>(entity).unwrap().settings, settings);
+
+        app.update();
+        let handle = app.world.get::<Handle<Svg>>(entity).unwrap().clone();
+        assert_eq!(handle.path().unwrap().to_string(), path);
+        wait_for(&mut app, &handle);
+        app.update();
+        assert!(app.world.get::<SvgFileLoading>(entity).is_none());
+    }
+}
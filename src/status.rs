@@ -0,0 +1,285 @@
+use std::{collections::HashSet, sync::Arc};
+
+use bevy::{
+    asset::{AssetServer, Handle, LoadState, RecursiveDependencyLoadState},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        query::{Changed, Or, With},
+        reflect::ReflectComponent,
+        system::{Local, ParamSet, Query, Res},
+    },
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+use crate::{
+    loader::{FileSvgError, SvgLoadFailures},
+    svg::Svg,
+};
+
+#[derive(Clone, Component, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// The current state of the [`Svg`] of an entity.
+pub enum SvgStatus {
+    #[default]
+    /// The [`Svg`] is not yet loaded, so the entity has no mesh.
+    Loading,
+    /// The entity received the mesh of its [`Svg`].
+    Ready,
+    /// The [`Svg`] failed to load.
+    Failed,
+}
+
+#[derive(Clone, Debug, Event)]
+/// Sent when the mesh of an [`Svg`] was added to or replaced on an entity.
+pub enum SvgEvent {
+    /// The entity received the mesh of its [`Svg`] for the first time, either because the
+    /// [`Svg`] finished loading or because the [`Handle<Svg>`] of the entity changed.
+    Ready {
+        /// The entity that received the mesh.
+        entity: Entity,
+        /// The [`Svg`] the mesh belongs to.
+        handle: Handle<Svg>,
+    },
+    /// The [`Svg`] of the entity was modified with a new or changed mesh, e.g. by
+    /// hot-reloading, and the entity received the new mesh. Modifications which keep the
+    /// mesh, like changed [`Svg::path_styles`], don't send it.
+    Reloaded {
+        /// The entity that received the mesh.
+        entity: Entity,
+        /// The [`Svg`] the mesh belongs to.
+        handle: Handle<Svg>,
+    },
+}
+
+#[derive(Clone, Debug, Event)]
+/// Sent when the [`Svg`] of an entity failed to load.
+pub struct SvgLoadFailed {
+    /// The entity that uses the [`Svg`].
+    pub entity: Entity,
+    /// The [`Svg`] that failed to load.
+    pub handle: Handle<Svg>,
+    /// The error of the last failed load of the file of the [`Svg`], if it was reported
+    /// by the [`Svg`] asset loader and not by a dependency of it.
+    pub error: Option<Arc<FileSvgError>>,
+}
+
+type ChangedSvgStatus = (
+    With<SvgStatus>,
+    Or<(Changed<SvgStatus>, Changed<Handle<Svg>>)>,
+);
+
+type SvgStatusComponents = (&'static Handle<Svg>, &'static mut SvgStatus);
+
+/// Checks the SVGs which are still loading and marks them as failed, if their asset or one
+/// of its dependencies failed to load.
+///
+/// Only entities whose [`SvgStatus`] or [`Handle<Svg>`] changed are added to the checked
+/// entities, and they are checked until they are no longer [`SvgStatus::Loading`].
+pub(crate) fn detect_failed_loads(
+    asset_server: Res<AssetServer>,
+    failures: Res<SvgLoadFailures>,
+    mut failed_events: EventWriter<SvgLoadFailed>,
+    mut loading: Local<HashSet<Entity>>,
+    mut queries: ParamSet<(Query<Entity, ChangedSvgStatus>, Query<SvgStatusComponents>)>,
+) {
+    loading.extend(&queries.p0());
+    let mut query = queries.p1();
+    loading.retain(|&entity| {
+        let Ok((handle, mut status)) = query.get_mut(entity) else {
+            return false;
+        };
+        if *status != SvgStatus::Loading {
+            return false;
+        }
+        if asset_server.load_state(handle) != LoadState::Failed
+            && asset_server.recursive_dependency_load_state(handle)
+                != RecursiveDependencyLoadState::Failed
+        {
+            return true;
+        }
+        *status = SvgStatus::Failed;
+        failed_events.send(SvgLoadFailed {
+            entity,
+            handle: handle.clone(),
+            error: handle.path().and_then(|path| failures.get(path.path())),
+        });
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "2d")]
+    use bevy::sprite::Mesh2dHandle;
+    use bevy::{
+        app::{App, Update},
+        asset::Assets,
+        ecs::{
+            event::{Event, Events},
+            schedule::{apply_deferred, IntoSystemConfigs},
+        },
+        render::mesh::Mesh,
+    };
+
+    use super::*;
+    use crate::{
+        loader::tests::load,
+        plugin::{add_svg_components, svg_mesh_linker},
+        removal::SvgRemovalPolicy,
+        svg::SvgPathStyle,
+        SvgLoaderSettings,
+    };
+
+    const SQUARE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <rect width="5" height="5"/>
+    </svg>"#;
+
+    fn app(files: &[(&str, &str)]) -> App {
+        let mut app = crate::loader::tests::app(files);
+        app.add_event::<SvgEvent>()
+            .add_event::<SvgLoadFailed>()
+            .init_resource::<SvgRemovalPolicy>()
+            .add_systems(
+                Update,
+                (
+                    add_svg_components,
+                    apply_deferred,
+                    svg_mesh_linker,
+                    apply_deferred,
+                    detect_failed_loads,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    /// Removes and returns the events of type `E` sent since the last call.
+    fn events<E: Event>(app: &mut App) -> Vec<E> {
+        app.world.resource_mut::<Events<E>>().drain().collect()
+    }
+
+    /// Spawns an entity with the [`Svg`] at `path` and updates the app until its status is
+    /// no longer [`SvgStatus::Loading`]. Returns the entity, its handle and all events sent.
+    fn spawn(
+        app: &mut App,
+        path: &str,
+    ) -> (Entity, Handle<Svg>, Vec<SvgEvent>, Vec<SvgLoadFailed>) {
+        let handle = app.world.resource::<AssetServer>().load(path.to_string());
+        let entity = app.world.spawn(handle.clone()).id();
+        let (mut svg_events, mut failed_events) = (Vec::new(), Vec::new());
+        let start = std::time::Instant::now();
+        while start.elapsed().as_secs() < 30 {
+            app.update();
+            svg_events.extend(events::<SvgEvent>(app));
+            failed_events.extend(events::<SvgLoadFailed>(app));
+            if app.world.get::<SvgStatus>(entity) != Some(&SvgStatus::Loading) {
+                return (entity, handle, svg_events, failed_events);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("asset was not loaded in time");
+    }
+
+    #[test]
+    fn ready_when_loaded() {
+        let mut app = app(&[("a.svg", SQUARE)]);
+        let (entity, handle, svg_events, failed_events) = spawn(&mut app, "a.svg");
+        assert_eq!(app.world.get::<SvgStatus>(entity), Some(&SvgStatus::Ready));
+        assert!(matches!(
+            svg_events.as_slice(),
+            [SvgEvent::Ready { entity: e, handle: h }] if *e == entity && *h == handle
+        ));
+        assert!(failed_events.is_empty());
+    }
+
+    #[test]
+    fn ready_when_handle_changes() {
+        let mut app = app(&[("a.svg", SQUARE), ("b.svg", SQUARE)]);
+        let (entity, ..) = spawn(&mut app, "a.svg");
+        let handle = load(&mut app, "b.svg", SvgLoaderSettings::default());
+        events::<SvgEvent>(&mut app);
+        app.world.entity_mut(entity).insert(handle.clone());
+        app.update();
+        assert_eq!(app.world.get::<SvgStatus>(entity), Some(&SvgStatus::Ready));
+        assert!(matches!(
+            events::<SvgEvent>(&mut app).as_slice(),
+            [SvgEvent::Ready { entity: e, handle: h }] if *e == entity && *h == handle
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "2d")]
+    fn reloaded_when_mesh_changes() {
+        let mut app = app(&[("a.svg", SQUARE)]);
+        let (entity, handle, ..) = spawn(&mut app, "a.svg");
+        let mesh = Handle::weak_from_u128(1);
+        app.world
+            .resource_mut::<Assets<Svg>>()
+            .get_mut(&handle)
+            .unwrap()
+            .mesh = mesh.clone();
+        // The asset event is sent after the update of the app, so it is only handled in the next
+        app.update();
+        app.update();
+        assert_eq!(app.world.get::<SvgStatus>(entity), Some(&SvgStatus::Ready));
+        assert_eq!(
+            app.world.get::<Mesh2dHandle>(entity),
+            Some(&Mesh2dHandle(mesh))
+        );
+        assert!(matches!(
+            events::<SvgEvent>(&mut app).as_slice(),
+            [SvgEvent::Reloaded { entity: e, handle: h }] if *e == entity && *h == handle
+        ));
+    }
+
+    #[test]
+    fn reloaded_only_when_geometry_changes() {
+        let mut app = app(&[("a.svg", SQUARE)]);
+        let (entity, handle, ..) = spawn(&mut app, "a.svg");
+
+        // Path styles change the asset, but keep the geometry
+        let mut svgs = app.world.resource_mut::<Assets<Svg>>();
+        let svg = svgs.get_mut(&handle).unwrap();
+        svg.path_styles.insert(0, SvgPathStyle::default());
+        let mesh = svg.mesh.clone();
+        app.update();
+        app.update();
+        assert!(events::<SvgEvent>(&mut app).is_empty());
+
+        // A hot-reloaded SVG keeps its mesh handle, but modifies the mesh
+        app.world.resource_mut::<Assets<Mesh>>().get_mut(&mesh);
+        app.world.resource_mut::<Assets<Svg>>().get_mut(&handle);
+        app.update();
+        app.update();
+        assert!(matches!(
+            events::<SvgEvent>(&mut app).as_slice(),
+            [SvgEvent::Reloaded { entity: e, handle: h }] if *e == entity && *h == handle
+        ));
+    }
+
+    #[test]
+    fn failed_with_loader_error() {
+        let mut app = app(&[("broken.svg", "<svg")]);
+        let (entity, handle, svg_events, failed_events) = spawn(&mut app, "broken.svg");
+        assert_eq!(app.world.get::<SvgStatus>(entity), Some(&SvgStatus::Failed));
+        assert!(svg_events.is_empty());
+        assert!(matches!(
+            failed_events.as_slice(),
+            [SvgLoadFailed { entity: e, handle: h, error: Some(_) }]
+                if *e == entity && *h == handle
+        ));
+    }
+
+    #[test]
+    fn failed_without_file() {
+        let mut app = app(&[]);
+        let (entity, _, _, failed_events) = spawn(&mut app, "missing.svg");
+        assert_eq!(app.world.get::<SvgStatus>(entity), Some(&SvgStatus::Failed));
+        assert!(matches!(
+            failed_events.as_slice(),
+            [SvgLoadFailed { entity: e, error: None, .. }] if *e == entity
+        ));
+    }
+}
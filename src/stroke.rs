@@ -0,0 +1,200 @@
+//! Strokes with a width on screen, e.g. hairlines of maps or technical drawings.
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Or, With},
+        reflect::ReflectComponent,
+        system::{Query, ResMut},
+    },
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::Extract,
+};
+
+#[cfg(feature = "2d")]
+use bevy::sprite::RenderMesh2dInstances;
+
+#[cfg(feature = "3d")]
+use bevy::pbr::RenderMeshInstances;
+
+// Keep in sync with the `SVG_MESH_FLAGS_*_STROKE*` constants in svg_material.wgsl. The bits are
+// not used by Bevy, the billboards of the 3D SVGs use the bit below them.
+/// Bit of the mesh flags of entities whose strokes all have a width on screen.
+const MESH_FLAGS_NON_SCALING_STROKES: u32 = 1 << 25;
+/// Bit of the mesh flags of entities whose strokes are all [`SvgStrokeWidth::Pixels`] wide.
+const MESH_FLAGS_PIXEL_STROKES: u32 = 1 << 26;
+/// Bit of the mesh flags of entities whose strokes are scaled by a [`SvgStrokeScale`].
+const MESH_FLAGS_SCALED_STROKES: u32 = 1 << 27;
+/// The width of [`SvgStrokeWidth::Pixels`] is stored in sixteenths of a pixel in the bits
+/// of the mesh flags from this one on, otherwise the [`SvgStrokeScale`] in 256ths.
+const MESH_FLAGS_STROKE_PIXELS_SHIFT: u32 = 8;
+
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// How wide the strokes of an SVG entity are drawn, independent of the scale of the entity and
+/// the zoom of the camera.
+///
+/// Strokes with a width on screen are widened in the vertex shader, so the mesh of the [`Svg`]
+/// needs [`ATTRIBUTE_STROKE_NORMAL`], see
+/// [`SvgTessellation::stroke_normals`](crate::svg::SvgTessellation::stroke_normals). The
+/// widths are still multiplied with [`Svg::stroke_width`] and
+/// [`SvgPathStyle::stroke_width`](crate::svg::SvgPathStyle::stroke_width).
+///
+/// The entity keeps the mesh of its [`Svg`], the stroke width is passed to the shader with the
+/// transform of the entity.
+///
+/// [`Svg`]: crate::svg::Svg
+/// [`Svg::stroke_width`]: crate::svg::Svg::stroke_width
+/// [`ATTRIBUTE_STROKE_NORMAL`]: crate::svg::ATTRIBUTE_STROKE_NORMAL
+pub enum SvgStrokeWidth {
+    #[default]
+    /// The strokes scale with the SVG, apart from those with
+    /// `vector-effect="non-scaling-stroke"`, this is the default.
+    Scaling,
+    /// All strokes behave as if they had `vector-effect="non-scaling-stroke"`. Their width in
+    /// the mesh is their width in physical pixels.
+    NonScaling,
+    /// All strokes are this many physical pixels wide, `Pixels(1.0)` draws hairlines. The
+    /// width is rounded to a sixteenth of a pixel and is less than 4096 pixels.
+    Pixels(f32),
+}
+
+#[derive(Clone, Component, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+/// Multiplies the width of the strokes of an SVG entity, together with [`Svg::stroke_width`]
+/// and [`SvgStrokeWidth`], e.g. to animate the strokes of one entity.
+///
+/// Like [`SvgStrokeWidth`], it needs [`ATTRIBUTE_STROKE_NORMAL`] in the mesh of the
+/// [`Svg`] and is passed to the shader with the transform of the entity, so the [`Svg`] is
+/// not modified. The scale is rounded to a 256th and is less than 256.
+///
+/// [`Svg`]: crate::svg::Svg
+/// [`Svg::stroke_width`]: crate::svg::Svg::stroke_width
+/// [`ATTRIBUTE_STROKE_NORMAL`]: crate::svg::ATTRIBUTE_STROKE_NORMAL
+pub struct SvgStrokeScale(pub f32);
+
+impl Default for SvgStrokeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl SvgStrokeWidth {
+    /// The bits of the mesh flags which pass this stroke width, multiplied with the
+    /// [`SvgStrokeScale`] if there is one, to the vertex shader.
+    fn mesh_flags(self, scale: Option<SvgStrokeScale>) -> u32 {
+        let scale_flags = scale.map_or(0, |SvgStrokeScale(scale)| {
+            MESH_FLAGS_SCALED_STROKES
+                | (fixed_point(scale, 256.0) << MESH_FLAGS_STROKE_PIXELS_SHIFT)
+        });
+        match self {
+            Self::Scaling => scale_flags,
+            Self::NonScaling => MESH_FLAGS_NON_SCALING_STROKES | scale_flags,
+            Self::Pixels(pixels) => {
+                let scale = scale.unwrap_or_default().0;
+                let sixteenths = fixed_point(pixels * scale, 16.0);
+                MESH_FLAGS_PIXEL_STROKES | (sixteenths << MESH_FLAGS_STROKE_PIXELS_SHIFT)
+            }
+        }
+    }
+}
+
+/// `value` in steps of `1 / steps`, clamped to the 16 bits of the mesh flags which are left
+/// for it.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn fixed_point(value: f32, steps: f32) -> u32 {
+    (value * steps).round().clamp(0.0, f32::from(u16::MAX)) as u32
+}
+
+type StrokeComponents = (
+    Entity,
+    Option<&'static SvgStrokeWidth>,
+    Option<&'static SvgStrokeScale>,
+);
+type WithStroke = Or<(With<SvgStrokeWidth>, With<SvgStrokeScale>)>;
+
+/// The mesh flags of an entity with a [`SvgStrokeWidth`] or [`SvgStrokeScale`].
+fn mesh_flags(stroke_width: Option<&SvgStrokeWidth>, scale: Option<&SvgStrokeScale>) -> u32 {
+    let stroke_width = stroke_width.copied().unwrap_or_default();
+    stroke_width.mesh_flags(scale.copied())
+}
+
+/// Passes the [`SvgStrokeWidth`] and [`SvgStrokeScale`] of every visible 2D entity to the
+/// vertex shader.
+#[cfg(feature = "2d")]
+pub(crate) fn extract_stroke_widths_2d(
+    strokes: Extract<Query<StrokeComponents, WithStroke>>,
+    mut mesh_instances: ResMut<RenderMesh2dInstances>,
+) {
+    for (entity, stroke_width, scale) in &strokes {
+        if let Some(mesh_instance) = mesh_instances.get_mut(&entity) {
+            mesh_instance.transforms.flags |= mesh_flags(stroke_width, scale);
+        }
+    }
+}
+
+/// Passes the [`SvgStrokeWidth`] and [`SvgStrokeScale`] of every visible 3D entity to the
+/// vertex shader.
+#[cfg(feature = "3d")]
+pub(crate) fn extract_stroke_widths_3d(
+    strokes: Extract<Query<StrokeComponents, WithStroke>>,
+    mut mesh_instances: ResMut<RenderMeshInstances>,
+) {
+    for (entity, stroke_width, scale) in &strokes {
+        if let Some(mesh_instance) = mesh_instances.get_mut(&entity) {
+            mesh_instance.transforms.flags |= mesh_flags(stroke_width, scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_are_stored_in_sixteenths() {
+        assert_eq!(SvgStrokeWidth::Scaling.mesh_flags(None), 0);
+        assert_eq!(
+            SvgStrokeWidth::NonScaling.mesh_flags(None),
+            MESH_FLAGS_NON_SCALING_STROKES
+        );
+        let flags = SvgStrokeWidth::Pixels(1.5).mesh_flags(None);
+        assert_eq!(flags & MESH_FLAGS_PIXEL_STROKES, MESH_FLAGS_PIXEL_STROKES);
+        assert_eq!((flags >> MESH_FLAGS_STROKE_PIXELS_SHIFT) & 0xFFFF, 24);
+
+        // The width never reaches the flags of Bevy or the other bits of this crate
+        for pixels in [-1.0, 0.0, 1e9, f32::NAN] {
+            let flags = SvgStrokeWidth::Pixels(pixels).mesh_flags(None);
+            assert_eq!(flags & !(MESH_FLAGS_PIXEL_STROKES | (0xFFFF << 8)), 0);
+        }
+    }
+
+    #[test]
+    fn scale_is_stored_in_256ths() {
+        let scale = |flags: u32| (flags >> MESH_FLAGS_STROKE_PIXELS_SHIFT) & 0xFFFF;
+        let flags = SvgStrokeWidth::Scaling.mesh_flags(Some(SvgStrokeScale(0.5)));
+        assert_eq!(flags & !(0xFFFF << 8), MESH_FLAGS_SCALED_STROKES);
+        assert_eq!(scale(flags), 128);
+        let flags = SvgStrokeWidth::NonScaling.mesh_flags(Some(SvgStrokeScale(2.0)));
+        assert_eq!(
+            flags & !(0xFFFF << 8),
+            MESH_FLAGS_NON_SCALING_STROKES | MESH_FLAGS_SCALED_STROKES
+        );
+        assert_eq!(scale(flags), 512);
+
+        // Widths in pixels are scaled before they are stored
+        let flags = SvgStrokeWidth::Pixels(1.5).mesh_flags(Some(SvgStrokeScale(2.0)));
+        assert_eq!(flags & !(0xFFFF << 8), MESH_FLAGS_PIXEL_STROKES);
+        assert_eq!(scale(flags), 48);
+
+        for value in [-1.0, 0.0, 1e9, f32::NAN] {
+            let flags = SvgStrokeWidth::Scaling.mesh_flags(Some(SvgStrokeScale(value)));
+            assert_eq!(flags & !(MESH_FLAGS_SCALED_STROKES | (0xFFFF << 8)), 0);
+        }
+        assert_eq!(
+            mesh_flags(None, Some(&SvgStrokeScale(0.5))),
+            SvgStrokeWidth::Scaling.mesh_flags(Some(SvgStrokeScale(0.5)))
+        );
+    }
+}
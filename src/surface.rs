@@ -0,0 +1,225 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::{Vec2, Vec3},
+    reflect::{std_traits::ReflectDefault, Reflect},
+};
+use serde::{Deserialize, Serialize};
+
+/// A curved surface that the mesh of an [`Svg`](crate::svg::Svg) is bent onto, see
+/// [`Svg::tessellate_onto`](crate::svg::Svg::tessellate_onto).
+///
+/// Points are given relative to the center of the viewBox in mesh coordinates, i.e. in SVG
+/// units with the y-axis pointing up, and the surface returns where they end up relative to
+/// that center. A surface should keep the center in place and face along the z-axis there, so
+/// the bent SVG stays where the flat one was. It is implemented for closures, e.g.
+/// `|p: Vec2| Vec3::new(p.x, p.y, (p.x * 0.1).sin())` for a waving flag.
+pub trait SvgSurface {
+    /// The position of `point` on the surface.
+    fn position(&self, point: Vec2) -> Vec3;
+
+    /// The normal of the surface at `point`, on the side the SVG is seen from. The default
+    /// implementation derives it from the neighbouring positions.
+    fn normal(&self, point: Vec2) -> Vec3 {
+        const EPSILON: f32 = 0.01;
+        let dx =
+            self.position(point + Vec2::X * EPSILON) - self.position(point - Vec2::X * EPSILON);
+        let dy =
+            self.position(point + Vec2::Y * EPSILON) - self.position(point - Vec2::Y * EPSILON);
+        dx.cross(dy).normalize_or_zero()
+    }
+}
+
+impl<F: Fn(Vec2) -> Vec3> SvgSurface for F {
+    fn position(&self, point: Vec2) -> Vec3 {
+        self(point)
+    }
+}
+
+/// A cylinder or sphere that the mesh of an [`Svg`](crate::svg::Svg) is bent onto, e.g. for
+/// labels on bottles, banners on columns or a curved HUD, set it with
+/// [`Svg3dSettings::wrap`](crate::svg::Svg3dSettings::wrap).
+///
+/// The SVG touches the surface at its center and is bent away from the viewer, i.e. towards
+/// the negative z-axis. A negative radius bends it towards the viewer instead, e.g. for a
+/// HUD that curves around the camera. The radius must not be zero.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgWrap {
+    /// A cylinder along the y-axis, the SVG is rolled around it horizontally. An SVG as wide
+    /// as the circumference covers the whole cylinder, this is the default with a radius of
+    /// `1.0`.
+    Cylinder {
+        /// The radius of the cylinder in SVG units.
+        radius: f32,
+    },
+    /// A sphere, the SVG is bent around it horizontally and vertically. The x-axis of the
+    /// SVG follows the longitude and the y-axis the latitude, so the SVG is stretched towards
+    /// the poles.
+    Sphere {
+        /// The radius of the sphere in SVG units.
+        radius: f32,
+    },
+}
+
+impl Default for SvgWrap {
+    fn default() -> Self {
+        Self::Cylinder { radius: 1.0 }
+    }
+}
+
+impl SvgWrap {
+    /// The maximum length of the triangle edges of a bent mesh, so that every edge covers
+    /// at most 1/48 of the circumference of the surface.
+    pub fn max_edge_length(&self) -> f32 {
+        let (Self::Cylinder { radius } | Self::Sphere { radius }) = self;
+        radius.abs() * TAU / 48.0
+    }
+}
+
+impl SvgSurface for SvgWrap {
+    fn position(&self, point: Vec2) -> Vec3 {
+        match *self {
+            Self::Cylinder { radius } => {
+                let angle = point.x / radius;
+                Vec3::new(radius * angle.sin(), point.y, radius * angle.cos() - radius)
+            }
+            Self::Sphere { radius } => self.normal(point) * radius - Vec3::Z * radius,
+        }
+    }
+
+    fn normal(&self, point: Vec2) -> Vec3 {
+        match *self {
+            Self::Cylinder { radius } => {
+                let angle = point.x / radius;
+                Vec3::new(angle.sin(), 0.0, angle.cos())
+            }
+            Self::Sphere { radius } => {
+                let longitude = point.x / radius;
+                let latitude = point.y / radius;
+                Vec3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    latitude.cos() * longitude.cos(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use bevy::render::mesh::{Mesh, VertexAttributeValues};
+
+    use super::*;
+    use crate::svg::Svg;
+
+    fn assert_near(actual: Vec3, expected: Vec3) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-5),
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn cylinder_keeps_arc_lengths() {
+        let cylinder = SvgWrap::Cylinder { radius: 2.0 };
+        assert_near(
+            cylinder.position(Vec2::new(0.0, 3.0)),
+            Vec3::new(0.0, 3.0, 0.0),
+        );
+        assert_near(cylinder.normal(Vec2::new(0.0, 3.0)), Vec3::Z);
+        // A quarter of the circumference away, the surface faces along the x-axis
+        assert_near(
+            cylinder.position(Vec2::new(PI, 1.0)),
+            Vec3::new(2.0, 1.0, -2.0),
+        );
+        assert_near(cylinder.normal(Vec2::new(PI, 1.0)), Vec3::X);
+
+        // A negative radius bends the SVG towards the viewer
+        let hud = SvgWrap::Cylinder { radius: -2.0 };
+        assert_near(hud.position(Vec2::new(PI, 0.0)), Vec3::new(2.0, 0.0, 2.0));
+        assert_near(hud.normal(Vec2::new(PI, 0.0)), Vec3::NEG_X);
+        assert_eq!(hud.max_edge_length(), cylinder.max_edge_length());
+    }
+
+    #[test]
+    fn sphere_maps_longitude_and_latitude() {
+        let sphere = SvgWrap::Sphere { radius: 1.0 };
+        assert_near(sphere.position(Vec2::ZERO), Vec3::ZERO);
+        assert_near(
+            sphere.position(Vec2::new(FRAC_PI_2, 0.0)),
+            Vec3::new(1.0, 0.0, -1.0),
+        );
+        assert_near(
+            sphere.position(Vec2::new(0.0, FRAC_PI_2)),
+            Vec3::new(0.0, 1.0, -1.0),
+        );
+        assert_near(sphere.normal(Vec2::new(0.0, FRAC_PI_2)), Vec3::Y);
+        // At the pole, the longitude no longer moves the point
+        assert_near(
+            sphere.position(Vec2::new(1.0, FRAC_PI_2)),
+            Vec3::new(0.0, 1.0, -1.0),
+        );
+    }
+
+    #[test]
+    fn parametric_surfaces_derive_their_normals() {
+        let slope = |p: Vec2| Vec3::new(p.x, p.y, p.x * 0.5);
+        assert_near(
+            slope.position(Vec2::new(2.0, 3.0)),
+            Vec3::new(2.0, 3.0, 1.0),
+        );
+        assert_near(
+            slope.normal(Vec2::new(2.0, 3.0)),
+            Vec3::new(-0.5, 0.0, 1.0).normalize(),
+        );
+
+        // The derived normals match the exact normals of the wraps
+        for wrap in [
+            SvgWrap::Cylinder { radius: 3.0 },
+            SvgWrap::Sphere { radius: 3.0 },
+        ] {
+            let parametric = |p: Vec2| wrap.position(p);
+            for point in [Vec2::ZERO, Vec2::new(1.0, 0.5), Vec2::new(-2.0, -1.0)] {
+                assert!(parametric
+                    .normal(point)
+                    .abs_diff_eq(wrap.normal(point), 1e-3));
+            }
+        }
+    }
+
+    #[test]
+    fn subdivision_bounds_edges_on_curved_surfaces() {
+        let svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 10">
+                <rect width="40" height="10"/>
+            </svg>"#,
+            "label.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let mesh = svg.tessellate_onto(&SvgWrap::Cylinder { radius: 10.0 }, 1.0);
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("the bent mesh has 3D positions");
+        };
+        assert!(positions.iter().any(|p| p[2] < -1.0), "the mesh is bent");
+
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        assert!(indices.len() > 40 * 10 * 3);
+        for triangle in indices.chunks_exact(3) {
+            for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+                let from = Vec3::from(positions[triangle[from]]);
+                let to = Vec3::from(positions[triangle[to]]);
+                assert!(
+                    from.distance(to) <= 1.0 + 1e-4,
+                    "{from} to {to} is too long"
+                );
+            }
+        }
+    }
+}
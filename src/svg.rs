@@ -1,23 +1,40 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use bevy::{
     asset::{Asset, Handle},
-    math::{Mat4, Vec2},
+    math::{Mat4, Rect, Vec2, Vec3},
     reflect::{std_traits::ReflectDefault, Reflect, TypeUuid},
-    render::{color::Color, mesh::Mesh, render_resource::AsBindGroup},
+    render::{
+        color::Color,
+        mesh::{Mesh, MeshVertexAttribute},
+        render_resource::VertexFormat,
+    },
     transform::components::Transform,
 };
 use copyless::VecHelper;
 use lyon_geom::euclid::default::Transform2D;
 use lyon_path::PathEvent;
 use lyon_tessellation::{math::Point, FillTessellator, StrokeTessellator};
-use svgtypes::ViewBox;
+use serde::{Deserialize, Serialize};
 use usvg::NodeExt;
 
-use crate::{loader::FileSvgError, render::tessellation, Convert};
+use crate::{
+    loader::{FileSvgError, LayerFilter},
+    markers::{MarkerSelector, SvgMarker},
+    render::{
+        tessellation,
+        vertex_buffer::{self, SvgVertexConstructor, VertexBuffers},
+    },
+    surface::{SvgSurface, SvgWrap},
+    xml::ElementAttributes,
+    Convert,
+};
 
 /// A loaded and deserialized SVG file.
-#[derive(AsBindGroup, Reflect, Debug, Clone, TypeUuid, Asset)]
+#[derive(Reflect, Debug, Clone, TypeUuid, Asset)]
 #[reflect(Default, Debug)]
 #[uuid = "ad47a360-355d-4955-9fd8-678412a77f12"]
 pub struct Svg {
@@ -25,12 +42,26 @@ pub struct Svg {
     pub name: String,
     /// Size of the SVG.
     pub size: Vec2,
-    #[reflect(ignore)]
-    /// ViewBox of the SVG.
+    /// `ViewBox` of the SVG.
     pub view_box: ViewBox,
-    #[reflect(ignore)]
+    /// All groups of the SVG in document order.
+    pub groups: Vec<GroupDescriptor>,
     /// All paths that make up the SVG.
     pub paths: Vec<PathDescriptor>,
+    /// All marker shapes of the SVG in document order, see [`Svg::markers`].
+    pub markers: Vec<SvgMarker>,
+    /// How the SVG is rendered in 3D.
+    pub render_3d: Svg3dSettings,
+    /// How finely the paths of the SVG are tessellated.
+    pub tessellation: SvgTessellation,
+    /// Overrides of the color, opacity and visibility of paths by their
+    /// [`SvgTessellation::index`], applied when the SVG is rendered.
+    pub path_styles: HashMap<u32, SvgPathStyle>,
+    /// Multiplies the width of all strokes, applied when the SVG is rendered if the mesh has
+    /// [`SvgTessellation::stroke_normals`]. `1.0` by default. A
+    /// [`SvgStrokeScale`](crate::stroke::SvgStrokeScale) scales the strokes of a single
+    /// entity instead.
+    pub stroke_width: f32,
     /// The fully tessellated paths as [`Mesh`].
     pub mesh: Handle<Mesh>,
 }
@@ -38,66 +69,523 @@ pub struct Svg {
 impl Default for Svg {
     fn default() -> Self {
         Self {
-            name: Default::default(),
-            size: Default::default(),
-            view_box: ViewBox {
-                x: 0.,
-                y: 0.,
-                w: 0.,
-                h: 0.,
-            },
-            paths: Default::default(),
-            mesh: Default::default(),
+            name: String::new(),
+            size: Vec2::ZERO,
+            view_box: ViewBox::default(),
+            groups: Vec::new(),
+            paths: Vec::new(),
+            markers: Vec::new(),
+            render_3d: Svg3dSettings::default(),
+            tessellation: SvgTessellation::default(),
+            path_styles: HashMap::new(),
+            stroke_width: 1.0,
+            mesh: Handle::default(),
         }
     }
 }
 
+/// The viewBox of an SVG, i.e. the area of the SVG user space that is visible.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default, Debug)]
+pub struct ViewBox {
+    /// X coordinate of the top left corner.
+    pub x: f64,
+    /// Y coordinate of the top left corner.
+    pub y: f64,
+    /// Width of the viewBox.
+    pub w: f64,
+    /// Height of the viewBox.
+    pub h: f64,
+}
+
 impl Svg {
     /// Loads an SVG from bytes
     pub fn from_bytes(
         bytes: &[u8],
         path: impl Into<PathBuf>,
         fonts: Option<impl Into<PathBuf>>,
-    ) -> Result<Svg, FileSvgError> {
-        let mut opts = usvg::Options::default();
-        opts.fontdb.load_system_fonts();
-        opts.fontdb
-            .load_fonts_dir(fonts.map(|p| p.into()).unwrap_or("./assets".into()));
+    ) -> Result<Self, FileSvgError> {
+        let opts = Self::options(fonts);
+        let parse = || {
+            let text = crate::xml::name_elements(crate::xml::decode(bytes)?)?;
+            let doc = crate::xml::parse(&text)?;
+            let svg_tree = usvg::Tree::from_xmltree(&doc, &opts.to_ref())?;
+            Ok::<_, usvg::Error>(Self::from_tree(svg_tree, &ElementAttributes::collect(&doc)))
+        };
+
+        parse().map_err(|err| FileSvgError {
+            error: err.into(),
+            path: format!("{}", path.into().display()),
+        })
+    }
+
+    /// Loads every top-level `<symbol>` of an SVG from bytes as its own [`Svg`], together
+    /// with the `id` of the symbol.
+    ///
+    /// The size and viewBox of each [`Svg`] are taken from the `viewBox` of its symbol,
+    /// symbols without an `id` or a `viewBox` are skipped. The meshes are not yet created,
+    /// use [`Svg::tessellate`] for that.
+    pub fn symbols_from_bytes(
+        bytes: &[u8],
+        path: impl Into<PathBuf>,
+        fonts: Option<impl Into<PathBuf>>,
+    ) -> Result<Vec<(String, Self)>, FileSvgError> {
+        let path = path.into();
+        let parse = || {
+            let text = crate::xml::name_elements(crate::xml::decode(bytes)?)?;
+            let doc = crate::xml::parse(&text)?;
+            let symbols = crate::xml::symbols(&doc, &text);
+            if symbols.is_empty() {
+                // Loading the fonts is expensive, so it is skipped for files without symbols
+                return Ok(Vec::new());
+            }
+            Self::from_symbols(symbols, &path, &Self::options(fonts))
+        };
 
-        let svg_tree =
-            usvg::Tree::from_data(&bytes, &opts.to_ref()).map_err(|err| FileSvgError {
-                error: err.into(),
-                path: format!("{}", path.into().display()),
-            })?;
+        parse().map_err(|err| FileSvgError {
+            error: err.into(),
+            path: format!("{}", path.display()),
+        })
+    }
 
-        Ok(Svg::from_tree(svg_tree))
+    /// Loads an SVG like [`Svg::from_bytes`] together with its symbols like
+    /// [`Svg::symbols_from_bytes`], but parses the document only once.
+    pub(crate) fn with_symbols_from_bytes(
+        bytes: &[u8],
+        path: impl Into<PathBuf>,
+        fonts: Option<impl Into<PathBuf>>,
+    ) -> Result<(Self, Vec<(String, Self)>), FileSvgError> {
+        let path = path.into();
+        let opts = Self::options(fonts);
+        let parse = || {
+            let text = crate::xml::name_elements(crate::xml::decode(bytes)?)?;
+            let doc = crate::xml::parse(&text)?;
+            let symbols = Self::from_symbols(crate::xml::symbols(&doc, &text), &path, &opts)?;
+            let svg_tree = usvg::Tree::from_xmltree(&doc, &opts.to_ref())?;
+            let svg = Self::from_tree(svg_tree, &ElementAttributes::collect(&doc));
+            Ok::<_, usvg::Error>((svg, symbols))
+        };
+
+        parse().map_err(|err| FileSvgError {
+            error: err.into(),
+            path: format!("{}", path.display()),
+        })
     }
 
-    /// Creates a bevy mesh from the SVG data.
+    /// Creates an [`Svg`] from the document of every symbol, named after `path` and its `id`.
+    fn from_symbols(
+        symbols: Vec<crate::xml::Symbol>,
+        path: &Path,
+        opts: &usvg::Options,
+    ) -> Result<Vec<(String, Self)>, usvg::Error> {
+        let mut svgs = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let symbol_doc = crate::xml::parse(&symbol.document)?;
+            let svg_tree = usvg::Tree::from_xmltree(&symbol_doc, &opts.to_ref())?;
+            let mut svg = Self::from_tree(svg_tree, &ElementAttributes::collect(&symbol_doc));
+            svg.name = format!(
+                "{}#Icon/{}",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                symbol.id
+            );
+            svgs.push((symbol.id, svg));
+        }
+        Ok(svgs)
+    }
+
+    /// The `usvg` options used to parse SVGs, with fonts loaded from the system and `fonts`.
+    fn options(fonts: Option<impl Into<PathBuf>>) -> usvg::Options {
+        // Named groups are kept, so the structure of the document is preserved.
+        let mut opts = usvg::Options {
+            keep_named_groups: true,
+            ..Default::default()
+        };
+        opts.fontdb.load_system_fonts();
+        opts.fontdb
+            .load_fonts_dir(fonts.map_or_else(|| PathBuf::from("./assets"), Into::into));
+        opts
+    }
+
+    /// Creates a bevy mesh from the SVG data, bent onto the [`Svg3dSettings::wrap`] if there
+    /// is one.
     pub fn tessellate(&self) -> Mesh {
+        self.tessellate_with(&mut ())
+    }
+
+    /// Creates a bevy mesh from the SVG data like [`Svg::tessellate`], with the custom vertex
+    /// attributes of `constructor`. Assign the mesh to [`Svg::mesh`] to draw the SVG with it,
+    /// e.g. with a custom material which reads the attributes.
+    pub fn tessellate_with<C: SvgVertexConstructor>(&self, constructor: &mut C) -> Mesh {
+        if let Some(wrap) = self.render_3d.wrap {
+            return self.tessellate_onto_with(&wrap, wrap.max_edge_length(), constructor);
+        }
+        // Strokes with `vector-effect="non-scaling-stroke"` are widened in the vertex shader
+        let settings = SvgTessellation {
+            stroke_normals: self.tessellation.stroke_normals
+                || self.paths.iter().any(|path| path.non_scaling_stroke),
+            ..self.tessellation
+        };
         let buffer = tessellation::generate_buffer(
             self,
             &mut FillTessellator::new(),
             &mut StrokeTessellator::new(),
+            &settings,
+            constructor,
         );
-        buffer.convert()
+        self.mesh_from(buffer, constructor, &settings)
+    }
+
+    /// Creates a bevy mesh from the SVG data, bent onto an arbitrary `surface` around the
+    /// center of the SVG.
+    ///
+    /// The triangles are split while they are tessellated, until none of their edges is
+    /// longer than `max_edge_length` in the user space of the SVG or the [`SvgTessellation`]
+    /// allows, so straight edges stay smooth after bending. The mesh has normals, which are
+    /// used by [`SvgLighting::Lit`], and the UVs of the flat SVG.
+    pub fn tessellate_onto(&self, surface: &impl SvgSurface, max_edge_length: f32) -> Mesh {
+        self.tessellate_onto_with(surface, max_edge_length, &mut ())
+    }
+
+    /// Creates a bevy mesh from the SVG data bent onto `surface` like
+    /// [`Svg::tessellate_onto`], with the custom vertex attributes of `constructor`.
+    pub fn tessellate_onto_with<C: SvgVertexConstructor>(
+        &self,
+        surface: &impl SvgSurface,
+        max_edge_length: f32,
+        constructor: &mut C,
+    ) -> Mesh {
+        // Bent meshes need 3D positions, and the stroke normals would no longer lie in the
+        // xy-plane
+        let settings = SvgTessellation {
+            max_edge_length: Some(
+                self.tessellation
+                    .max_edge_length
+                    .map_or(max_edge_length, |max| max.min(max_edge_length)),
+            ),
+            vertex_format: SvgVertexFormat::Standard,
+            stroke_normals: false,
+            ..self.tessellation
+        };
+        let mut buffer = tessellation::generate_buffer(
+            self,
+            &mut FillTessellator::new(),
+            &mut StrokeTessellator::new(),
+            &settings,
+            constructor,
+        );
+        let view_box = self.view_box;
+        let center = Vec2::new(
+            (view_box.x + view_box.w * 0.5) as f32,
+            -(view_box.y + view_box.h * 0.5) as f32,
+        );
+        let normals = tessellation::wrap(&mut buffer, surface, center);
+        let mut mesh = self.mesh_from(buffer, constructor, &settings);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh
+    }
+
+    /// Converts the tessellated paths into a mesh with the attributes that `settings` ask
+    /// for and the custom attributes of `constructor`.
+    fn mesh_from<C: SvgVertexConstructor>(
+        &self,
+        buffer: VertexBuffers<C::Vertex>,
+        constructor: &C,
+        settings: &SvgTessellation,
+    ) -> Mesh {
+        let indices = (settings.index != SvgIndexAttribute::None).then(|| {
+            buffer
+                .vertices
+                .iter()
+                .map(|vertex| vertex.index)
+                .collect::<Vec<_>>()
+        });
+        let stroke_normals = settings.stroke_normals.then(|| {
+            buffer
+                .vertices
+                .iter()
+                .map(|vertex| vertex.stroke)
+                .collect::<Vec<_>>()
+        });
+        let (mut mesh, custom) = match settings.vertex_format {
+            SvgVertexFormat::Standard => buffer.convert(),
+            SvgVertexFormat::Compact => vertex_buffer::into_compact_mesh(buffer),
+        };
+        if let Some(indices) = indices {
+            mesh.insert_attribute(ATTRIBUTE_PATH_INDEX, indices);
+        }
+        if let Some(stroke_normals) = stroke_normals {
+            mesh.insert_attribute(ATTRIBUTE_STROKE_NORMAL, stroke_normals);
+        }
+        constructor.insert_attributes(&mut mesh, custom);
+        mesh
+    }
+
+    /// Returns all paths created from the element with the given `id`, usually a fill and/or
+    /// a stroke.
+    pub fn paths_by_id<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a PathDescriptor> {
+        self.paths.iter().filter(move |path| path.id == id)
+    }
+
+    /// Returns all paths with the given `class`.
+    pub fn paths_by_class<'a>(
+        &'a self,
+        class: &'a str,
+    ) -> impl Iterator<Item = &'a PathDescriptor> {
+        self.paths
+            .iter()
+            .filter(move |path| path.class.iter().any(|c| c == class))
+    }
+
+    /// Returns all paths that are descendants of the group with the given `id`.
+    pub fn paths_in_group<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a PathDescriptor> {
+        self.paths
+            .iter()
+            .filter(move |path| self.ancestors(path.group).any(|group| group.id == id))
+    }
+
+    /// Returns the first group with the given `id`.
+    pub fn group_by_id(&self, id: &str) -> Option<&GroupDescriptor> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    /// Returns the chain of groups from the group with index `group` up to the root, e.g.
+    /// `svg.ancestors(path.group)` returns all groups containing `path`, innermost first.
+    /// The chain ends at the first index which is not in [`Svg::groups`].
+    pub fn ancestors(&self, group: Option<usize>) -> impl Iterator<Item = &GroupDescriptor> {
+        std::iter::successors(group.and_then(|g| self.groups.get(g)), |group| {
+            group.parent.and_then(|p| self.groups.get(p))
+        })
+    }
+
+    /// Returns the names of all layers in document order and without duplicates.
+    pub fn layer_names(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.groups
+            .iter()
+            .filter_map(|group| group.layer.as_deref())
+            .filter(|name| seen.insert(*name))
+            .collect()
+    }
+
+    /// Returns all paths that are descendants of the layer with the given `name`.
+    pub fn paths_in_layer<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a PathDescriptor> {
+        self.paths.iter().filter(move |path| {
+            self.ancestors(path.group)
+                .any(|group| group.layer.as_deref() == Some(name))
+        })
+    }
+
+    /// Creates an [`Svg`] that only contains the layer with the given `name`.
+    ///
+    /// Unlike [`Svg::element`], the paths keep their position, so the [`Svg`]s of all layers
+    /// line up with each other and have the size and viewBox of the whole document. The
+    /// mesh of the new [`Svg`] is not yet created, use [`Svg::tessellate`] for that.
+    pub fn layer(&self, name: &str) -> Option<Self> {
+        self.groups
+            .iter()
+            .any(|group| group.layer.as_deref() == Some(name))
+            .then(|| {
+                self.with_paths(
+                    format!("{}#layer/{name}", self.name),
+                    self.paths_in_layer(name).cloned().collect(),
+                )
+            })
     }
 
-    pub(crate) fn from_tree(tree: usvg::Tree) -> Svg {
+    /// Splits the SVG into one [`Svg`] per top-level layer, i.e. each layer that is not
+    /// nested in another layer, in document order. Paths outside of any layer are returned
+    /// as a last [`Svg`] with an empty name, if there are any.
+    ///
+    /// Like with [`Svg::layer`], all [`Svg`]s line up with each other. Their meshes are not
+    /// yet created, use [`Svg::tessellate`] for that.
+    pub fn top_level_layers(&self) -> Vec<(String, LayerDepth, Self)> {
+        let layer_of = |path: &PathDescriptor| {
+            self.ancestors(path.group)
+                .filter(|group| group.layer.is_some())
+                .last()
+        };
+
+        let mut layers: Vec<(String, LayerDepth, Self)> = Vec::new();
+        let mut rest = Vec::new();
+        for path in &self.paths {
+            let Some(group) = layer_of(path) else {
+                rest.push(path.clone());
+                continue;
+            };
+            let name = group.layer.as_deref().unwrap_or_default();
+            let index = layers
+                .iter()
+                .position(|(n, ..)| n == name)
+                .unwrap_or_else(|| {
+                    layers.push((
+                        name.to_string(),
+                        group.depth,
+                        self.with_paths(format!("{}#layer/{name}", self.name), Vec::new()),
+                    ));
+                    layers.len() - 1
+                });
+            layers[index].2.paths.push(path.clone());
+        }
+
+        if !rest.is_empty() {
+            layers.push((
+                String::new(),
+                LayerDepth::default(),
+                self.with_paths(self.name.clone(), rest),
+            ));
+        }
+        layers
+    }
+
+    /// Removes all paths of the layers which are not included by `filter`. Paths outside
+    /// of any layer and the [`Svg::markers`] are kept.
+    pub fn retain_layers(&mut self, filter: &LayerFilter) {
+        let groups = &self.groups;
+        self.paths.retain(|path| {
+            let mut layers = std::iter::successors(path.group.map(|g| &groups[g]), |group| {
+                group.parent.map(|p| &groups[p])
+            })
+            .filter_map(|group| group.layer.as_deref())
+            .peekable();
+            layers.peek().is_none() || filter.includes(layers)
+        });
+    }
+
+    /// Returns all markers selected by `selector`, e.g. the spawn points of a level with
+    /// `svg.markers(&MarkerSelector::IdPrefix("spawn_".into()))`.
+    pub fn markers<'a>(
+        &'a self,
+        selector: &'a MarkerSelector,
+    ) -> impl Iterator<Item = &'a SvgMarker> {
+        self.markers
+            .iter()
+            .filter(move |marker| selector.matches(marker))
+    }
+
+    /// Returns the ids of all groups followed by the ids of all path elements, each in
+    /// document order and without duplicates.
+    pub fn element_ids(&self) -> Vec<&str> {
+        let groups = self.groups.iter().map(|group| group.id.as_str());
+        let paths = self.paths.iter().map(|path| path.id.as_str());
+
+        let mut seen = HashSet::new();
+        groups
+            .chain(paths)
+            .filter(|id| !id.is_empty() && seen.insert(*id))
+            .collect()
+    }
+
+    /// Creates an [`Svg`] that only contains the group or path element with the given `id`.
+    ///
+    /// The paths are moved, so the top left corner of their bounding box is at the origin
+    /// and the size of the new [`Svg`] is the size of that bounding box. The mesh of the
+    /// new [`Svg`] is not yet created, use [`Svg::tessellate`] for that.
+    pub fn element(&self, id: &str) -> Option<Self> {
+        let mut paths: Vec<_> = if self.group_by_id(id).is_some() {
+            self.paths_in_group(id).cloned().collect()
+        } else {
+            self.paths_by_id(id).cloned().collect()
+        };
+        let bounds = bounding_box(&paths)?;
+
+        let offset = Transform::from_translation(-bounds.min.extend(0.0));
+        for path in &mut paths {
+            path.abs_transform = offset * path.abs_transform;
+            path.group = None;
+        }
+
+        Some(Self {
+            size: bounds.size(),
+            view_box: ViewBox {
+                x: 0.0,
+                y: 0.0,
+                w: f64::from(bounds.width()),
+                h: f64::from(bounds.height()),
+            },
+            groups: Vec::new(),
+            ..self.with_paths(format!("{}#{id}", self.name), paths)
+        })
+    }
+
+    /// Creates an [`Svg`] with the given `name` and `paths`, which keeps the size, viewBox,
+    /// groups and settings of this [`Svg`], but has no markers, path styles or mesh.
+    ///
+    /// The `paths` may refer to the groups of this [`Svg`]. The mesh of the new [`Svg`] is
+    /// not yet created, use [`Svg::tessellate`] for that.
+    pub fn with_paths(&self, name: String, paths: Vec<PathDescriptor>) -> Self {
+        Self {
+            name,
+            size: self.size,
+            view_box: self.view_box,
+            groups: self.groups.clone(),
+            paths,
+            markers: Vec::new(),
+            render_3d: self.render_3d,
+            tessellation: self.tessellation,
+            path_styles: HashMap::new(),
+            stroke_width: self.stroke_width,
+            mesh: Handle::default(),
+        }
+    }
+
+    pub(crate) fn from_tree(
+        tree: usvg::Tree,
+        attributes: &HashMap<String, ElementAttributes>,
+    ) -> Self {
         let view_box = tree.view_box;
         let size = tree.size;
-        let mut descriptors = Vec::new();
+        let mut svg = Self {
+            size: Vec2::new(size.width() as f32, size.height() as f32),
+            view_box: ViewBox {
+                x: view_box.rect.x(),
+                y: view_box.rect.y(),
+                w: view_box.rect.width(),
+                h: view_box.rect.height(),
+            },
+            ..Default::default()
+        };
+        svg.add_children(&tree.root, None, &mut 0, attributes);
 
-        for node in tree.root.descendants() {
-            match &*node.borrow() {
+        svg
+    }
+
+    /// Adds all descendants of `node` in paint order. `group` is the index of the innermost
+    /// group of `node` and `element` the index of the next path element.
+    fn add_children(
+        &mut self,
+        node: &usvg::Node,
+        group: Option<usize>,
+        element: &mut usize,
+        attributes: &HashMap<String, ElementAttributes>,
+    ) {
+        for child in node.children() {
+            match &*child.borrow() {
+                usvg::NodeKind::Group(g) => {
+                    let attrs = attributes.get(&g.id).cloned().unwrap_or_default();
+                    self.groups.alloc().init(GroupDescriptor {
+                        id: crate::xml::original_id(&g.id).to_string(),
+                        class: attrs.class,
+                        depth: LayerDepth::from_data(&attrs.data),
+                        data: attrs.data,
+                        layer: attrs.layer,
+                        parent: group,
+                        abs_transform: child.abs_transform().convert(),
+                    });
+                    let index = self.groups.len() - 1;
+                    self.add_children(&child, Some(index), element, attributes);
+                }
                 usvg::NodeKind::Path(path) => {
-                    let t = node.abs_transform();
-                    let abs_t = Transform::from_matrix(Mat4::from_cols(
-                        [t.a.abs() as f32, t.b as f32, 0.0, 0.0].into(),
-                        [t.c as f32, t.d.abs() as f32, 0.0, 0.0].into(),
-                        [0.0, 0.0, 1.0, 0.0].into(),
-                        [t.e as f32, t.f as f32, 0.0, 1.0].into(),
-                    ));
+                    let abs_t = child.abs_transform().convert();
+                    let attrs = attributes.get(&path.id).cloned().unwrap_or_default();
+                    let segments: Vec<PathSegment> = path.convert().map(Convert::convert).collect();
+                    // Shapes with a non-scaling stroke were only named to find their attributes
+                    let id = crate::xml::original_id(&path.id).to_string();
+
+                    if !id.is_empty() {
+                        if let Some(marker) = SvgMarker::from_path(&id, &attrs, &segments, abs_t) {
+                            self.markers.push(marker);
+                        }
+                    }
 
                     if let Some(fill) = &path.fill {
                         let color = match fill.paint {
@@ -107,56 +595,682 @@ impl Svg {
                             _ => Color::default(),
                         };
 
-                        descriptors.alloc().init(PathDescriptor {
-                            segments: path.convert().collect(),
+                        self.paths.alloc().init(PathDescriptor {
+                            id: id.clone(),
+                            class: attrs.class.clone(),
+                            data: attrs.data.clone(),
+                            segments: segments.clone(),
                             abs_transform: abs_t,
                             color,
                             draw_type: DrawType::Fill,
+                            group,
+                            element: *element,
+                            non_scaling_stroke: false,
                         });
                     }
 
                     if let Some(stroke) = &path.stroke {
                         let (color, draw_type) = stroke.convert();
 
-                        descriptors.alloc().init(PathDescriptor {
-                            segments: path.convert().collect(),
+                        self.paths.alloc().init(PathDescriptor {
+                            id,
+                            class: attrs.class,
+                            data: attrs.data,
+                            segments,
                             abs_transform: abs_t,
                             color,
                             draw_type,
+                            group,
+                            element: *element,
+                            non_scaling_stroke: attrs.non_scaling_stroke,
                         });
                     }
+                    *element += 1;
                 }
-                _ => {}
+                usvg::NodeKind::Image(_) => {}
             }
         }
+    }
+}
 
-        return Svg {
-            name: Default::default(),
-            size: Vec2::new(size.width() as f32, size.height() as f32),
-            view_box: ViewBox {
-                x: view_box.rect.x(),
-                y: view_box.rect.y(),
-                w: view_box.rect.width(),
-                h: view_box.rect.height(),
-            },
-            paths: descriptors,
-            mesh: Default::default(),
+/// Computes the bounding box of `paths` in SVG user space. The control points of curves are
+/// included, so the box might be slightly larger than the actual paths.
+pub(crate) fn bounding_box(paths: &[PathDescriptor]) -> Option<Rect> {
+    let mut bounds: Option<Rect> = None;
+    for path in paths {
+        let half_width = match &path.draw_type {
+            DrawType::Fill => 0.0,
+            DrawType::Stroke(opts) => opts.line_width * 0.5,
         };
+        let points = path.segments.iter().flat_map(|segment| match *segment {
+            PathSegment::Begin { at } => vec![at],
+            PathSegment::Line { to, .. } | PathSegment::End { last: to, .. } => vec![to],
+            PathSegment::Quadratic { ctrl, to, .. } => vec![ctrl, to],
+            PathSegment::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => vec![ctrl1, ctrl2, to],
+        });
+        for point in points {
+            let point = (path.abs_transform * Vec3::new(point.x, point.y, 0.0)).truncate();
+            let half_width = half_width * path.abs_transform.scale.truncate();
+            let rect = Rect::from_corners(point - half_width, point + half_width);
+            bounds = Some(bounds.map_or(rect, |bounds| bounds.union(rect)));
+        }
+    }
+    bounds
+}
+
+/// How an [`Svg`] is rendered in 3D, set them with
+/// [`SvgLoaderSettings::render_3d`](crate::loader::SvgLoaderSettings::render_3d).
+///
+/// The [`Svg`] is the material of its entities, so these settings apply to all entities
+/// using it. To render a single entity differently, give it a handle to a modified clone of
+/// the [`Svg`]. Changing the [`PaintOrder`] of a loaded [`Svg`] only takes effect after the
+/// mesh is tessellated again with [`Svg::tessellate`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub struct Svg3dSettings {
+    /// How overlapping paths are kept in paint order.
+    pub paint_order: PaintOrder,
+    /// How the colors of the SVG are blended with the geometry behind it.
+    pub alpha_mode: SvgAlphaMode,
+    /// Which faces are culled, the default culls the back faces, so the SVG is invisible
+    /// when seen from behind.
+    pub cull_mode: SvgCullMode,
+    /// Bias added to the depth of the SVG, e.g. to place it like a decal on a wall.
+    /// Positive values move it towards the camera. It also affects the order in which
+    /// the SVG is sorted against other geometry.
+    pub depth_bias: f32,
+    /// Whether the SVG casts shadows, off by default. Entities with a `NotShadowCaster`
+    /// never cast shadows. The shadow has the shape of the tessellated paths, with the
+    /// same alpha test as the [`SvgAlphaMode`]; blended SVGs only cast shadows where
+    /// their alpha is at least `0.05`.
+    pub cast_shadows: bool,
+    /// Whether the SVG is lit by the lights of the scene.
+    pub lighting: SvgLighting,
+    /// The cylinder or sphere the mesh is bent onto, none by default. Like the
+    /// [`PaintOrder`], it is applied when the mesh is tessellated. Use
+    /// [`Svg::tessellate_onto`] for other surfaces.
+    pub wrap: Option<SvgWrap>,
+}
+
+/// How finely the paths of an [`Svg`] are tessellated, set it with
+/// [`SvgLoaderSettings::tessellation`](crate::loader::SvgLoaderSettings::tessellation).
+///
+/// By default, filled paths only have vertices on their outlines and their triangles are
+/// often long and thin, so the interior can't be deformed in a vertex shader, e.g. to sway in
+/// the wind or wave like a flag. With a limit, the triangles of every path are split at the
+/// midpoints of their edges, which adds vertices inside of the paths.
+///
+/// The mesh has [`Mesh::ATTRIBUTE_UV_0`], which is set up by the [`SvgUvMode`] and the
+/// [`SvgStrokeUvMode`], unless it uses the [`SvgVertexFormat::Compact`]. Like the
+/// [`PaintOrder`], these settings are applied when the mesh is tessellated.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub struct SvgTessellation {
+    /// The maximum length of the edges of the triangles in the user space of the SVG, i.e.
+    /// in units of its viewBox after the transforms of the elements are applied.
+    pub max_edge_length: Option<f32>,
+    /// The maximum area of the triangles in square units of the user space of the SVG,
+    /// larger triangles are split along their longest edge.
+    pub max_triangle_area: Option<f32>,
+    /// The UVs of filled paths.
+    pub uv: SvgUvMode,
+    /// The UVs of stroked paths.
+    pub stroke_uv: SvgStrokeUvMode,
+    /// Which index the vertices get as [`ATTRIBUTE_PATH_INDEX`], none by default.
+    pub index: SvgIndexAttribute,
+    /// The attributes of the vertices.
+    pub vertex_format: SvgVertexFormat,
+    /// Whether the vertices get [`ATTRIBUTE_STROKE_NORMAL`], so the width of the strokes can
+    /// be changed with [`Svg::stroke_width`] and [`SvgPathStyle::stroke_width`] without
+    /// tessellating the mesh again. It is always added to meshes with strokes that have
+    /// `vector-effect="non-scaling-stroke"`. Meshes bent onto a [`SvgWrap`] don't get it,
+    /// their non-scaling strokes scale with the mesh.
+    pub stroke_normals: bool,
+}
+
+impl SvgTessellation {
+    /// Whether the triangles of the paths are split.
+    pub(crate) const fn subdivides(&self) -> bool {
+        self.max_edge_length.is_some() || self.max_triangle_area.is_some()
     }
 }
 
-#[derive(Debug, Clone)]
+/// Vertex attribute with the index of the path or element of every vertex, see
+/// [`SvgTessellation::index`]. It selects the [`SvgPathStyle`] of a vertex.
+pub const ATTRIBUTE_PATH_INDEX: MeshVertexAttribute = MeshVertexAttribute::new(
+    "SvgPathIndex",
+    2_806_351_079_425_179_063,
+    VertexFormat::Uint32,
+);
+
+/// Vertex attribute with the normal of the stroke of every vertex, see
+/// [`SvgTessellation::stroke_normals`].
+///
+/// The `xy` components are the normal in the mesh, pointing from the centerline of the stroke
+/// to the side of the vertex. It is longer than one at miter joins, so multiplied with the
+/// half width of the stroke in `z` it is the offset from the centerline. `w` is the side,
+/// `-1.0` on the left and `1.0` on the right, seen in the direction of the path. The
+/// vertices of filled paths have zeros.
+///
+/// A negative `z` is the negated half width in pixels on screen, for strokes with
+/// `vector-effect="non-scaling-stroke"`. Their vertices lie on the centerline and are moved
+/// along the normal as it is seen on screen by the vertex shader. A
+/// [`SvgStrokeWidth`](crate::stroke::SvgStrokeWidth) gives the strokes of an entity a width
+/// on screen in the vertex shader, without changing the mesh.
+pub const ATTRIBUTE_STROKE_NORMAL: MeshVertexAttribute = MeshVertexAttribute::new(
+    "SvgStrokeNormal",
+    2_806_351_079_425_179_111,
+    VertexFormat::Float32x4,
+);
+
+/// Which index the vertices of the mesh of an [`Svg`] get as [`ATTRIBUTE_PATH_INDEX`], see
+/// [`SvgTessellation::index`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgIndexAttribute {
+    #[default]
+    /// The mesh has no index attribute, this is the default.
+    None,
+    /// The index of the path in [`Svg::paths`], so the fill and the stroke of an element
+    /// have their own index.
+    Path,
+    /// The index of the element of the path, see [`PathDescriptor::element`], so the fill
+    /// and the stroke of an element share it.
+    Element,
+}
+
+/// Overrides how the paths with an index are drawn, see [`Svg::path_styles`], e.g. to
+/// highlight a region of a map or to pulse a single element.
+///
+/// The index of every vertex is taken from its [`ATTRIBUTE_PATH_INDEX`], so the mesh needs to
+/// be tessellated with a [`SvgIndexAttribute`]. The styles are applied in the vertex shader,
+/// changing them doesn't tessellate the mesh again. Only the indices below
+/// [`MAX_PATH_STYLES`] can be styled. SVGs without any style don't upload them to the GPU.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default, Debug)]
+pub struct SvgPathStyle {
+    /// The color which replaces the color of the paths, including its alpha.
+    pub color: Option<Color>,
+    /// Multiplied with the alpha of the paths.
+    pub opacity: f32,
+    /// Whether the paths are drawn.
+    pub visible: bool,
+    /// Multiplies the width of the strokes, together with [`Svg::stroke_width`]. Needs
+    /// [`SvgTessellation::stroke_normals`].
+    pub stroke_width: f32,
+}
+
+impl Default for SvgPathStyle {
+    fn default() -> Self {
+        Self {
+            color: None,
+            opacity: 1.0,
+            visible: true,
+            stroke_width: 1.0,
+        }
+    }
+}
+
+/// The number of path indices which can be styled with a [`SvgPathStyle`].
+pub const MAX_PATH_STYLES: usize = 256;
+
+/// The UVs of the filled paths of an [`Svg`], see [`SvgTessellation::uv`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgUvMode {
+    #[default]
+    /// The position in the viewBox of the SVG, from `(0, 0)` at the top left to `(1, 1)` at
+    /// the bottom right, this is the default.
+    ViewBox,
+    /// The position in the bounding box of every path, from `(0, 0)` at its top left to
+    /// `(1, 1)` at its bottom right, e.g. to give every region of a map its own texture.
+    PathBounds,
+}
+
+/// The UVs of the stroked paths of an [`Svg`], see [`SvgTessellation::stroke_uv`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgStrokeUvMode {
+    #[default]
+    /// The distance along the path in SVG units as `u`, and `v` going across the stroke from
+    /// `0` on its left to `1` on its right side, seen in the direction of the path. This
+    /// suits animated dashes or lines that flow along the path, this is the default.
+    AlongPath,
+    /// The same UVs as the filled paths, set by the [`SvgUvMode`].
+    Fill,
+}
+
+/// The attributes of the vertices of the mesh of an [`Svg`], see
+/// [`SvgTessellation::vertex_format`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgVertexFormat {
+    #[default]
+    /// [`Mesh::ATTRIBUTE_POSITION`], [`Mesh::ATTRIBUTE_COLOR`] in linear RGBA and
+    /// [`Mesh::ATTRIBUTE_UV_0`] with `u32` indices, 36 bytes per vertex, this is the default.
+    Standard,
+    /// [`ATTRIBUTE_POSITION_2D`] and [`ATTRIBUTE_COLOR_PACKED`], with `u16` indices if there
+    /// are at most 65536 vertices, 12 bytes per vertex. Suits large SVGs like maps.
+    ///
+    /// The mesh has no UVs, and the paths are no longer kept apart along the z-axis, so
+    /// overlapping paths in 3D need the default [`PaintOrder::NoDepthWrite`]. Without the built-in
+    /// positions, Bevy computes no bounds and the SVG is never frustum culled. Meshes bent
+    /// onto a [`SvgWrap`] keep the standard format.
+    Compact,
+}
+
+/// Vertex attribute of meshes with the [`SvgVertexFormat::Compact`], with the position in
+/// the xy-plane.
+pub const ATTRIBUTE_POSITION_2D: MeshVertexAttribute = MeshVertexAttribute::new(
+    "SvgPosition2d",
+    2_806_351_079_425_179_095,
+    VertexFormat::Float32x2,
+);
+
+/// Vertex attribute of meshes with the [`SvgVertexFormat::Compact`], with the color in sRGB
+/// and its alpha packed into four bytes. The colors of SVGs have eight bits per channel, so
+/// they are stored without loss.
+pub const ATTRIBUTE_COLOR_PACKED: MeshVertexAttribute = MeshVertexAttribute::new(
+    "SvgColorPacked",
+    2_806_351_079_425_179_103,
+    VertexFormat::Unorm8x4,
+);
+
+/// How the colors of an [`Svg`] are blended in 3D, mirroring Bevy's `AlphaMode`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgAlphaMode {
+    #[default]
+    /// The alpha channel is ignored, this is the default.
+    Opaque,
+    /// Pixels with an alpha below the threshold are discarded, all others are opaque.
+    Mask(f32),
+    /// The colors are blended with the geometry behind them by their alpha.
+    Blend,
+    /// Premultiplied alpha blending. The colors of an SVG are not premultiplied, so the
+    /// shader premultiplies them and the result looks like [`SvgAlphaMode::Blend`].
+    Premultiplied,
+    /// The colors are added to the geometry behind them, weighted by their alpha.
+    Add,
+    /// The colors are multiplied with the geometry behind them, weighted by their alpha.
+    Multiply,
+}
+
+/// Which faces of an [`Svg`] are culled in 3D.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgCullMode {
+    #[default]
+    /// Back faces are culled, this is the default.
+    Back,
+    /// Front faces are culled.
+    Front,
+    /// No faces are culled, the SVG is double-sided.
+    None,
+}
+
+/// How an [`Svg`] is lit in 3D.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum SvgLighting {
+    #[default]
+    /// The colors of the SVG are shown as they are, this is the default.
+    Unlit,
+    /// The colors of the SVG are the base color of a rough, non-metallic surface facing
+    /// along the z-axis of the entity, which is lit by the lights of the scene and receives
+    /// shadows, unless the entity has a `NotShadowReceiver`.
+    Lit,
+}
+
+/// How the overlapping paths of an [`Svg`] are kept in paint order in 3D, where they would
+/// otherwise z-fight, because they all lie in the same plane.
+///
+/// The choice affects how the SVG is sorted against other geometry of the scene.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub enum PaintOrder {
+    /// All paths lie at `z = 0` and the SVG doesn't write depth, so later paths are drawn
+    /// over earlier ones in the order of the mesh. It is still hidden behind other geometry,
+    /// but doesn't hide geometry drawn after it. This is the default.
+    #[default]
+    NoDepthWrite,
+    /// Every path is moved towards the front along the z-axis by this distance in SVG units
+    /// per path, so the SVG gets a small thickness. It writes depth and is sorted like any
+    /// other geometry.
+    ///
+    /// The offset is part of the mesh, so it also moves the paths of 2D entities using the
+    /// [`Svg`], where the last path of an SVG with many paths can end up behind the far
+    /// plane of the camera. Only use it for SVGs which are rendered in 3D, with a distance
+    /// that is small compared to their size, e.g. `0.01` for an SVG that is `100` units wide.
+    ZOffset(f32),
+}
+
+impl PaintOrder {
+    /// The distance along the z-axis between two consecutive paths.
+    pub const fn z_step(&self) -> f32 {
+        match self {
+            Self::ZOffset(step) => *step,
+            Self::NoDepthWrite => 0.0,
+        }
+    }
+}
+
+/// A `<g>` element of an [`Svg`].
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct GroupDescriptor {
+    /// The `id` attribute of the group, can be empty.
+    pub id: String,
+    /// The entries of the `class` attribute of the group.
+    pub class: Vec<String>,
+    /// The `data-*` attributes of the group, without the `data-` prefix.
+    pub data: HashMap<String, String>,
+    /// The name of the layer, if the group is an Inkscape layer or a top-level group of a
+    /// document without Inkscape layers, like the layers exported by Illustrator.
+    pub layer: Option<String>,
+    /// The depth of the layer, see [`LayerDepth`].
+    pub depth: LayerDepth,
+    /// Index of the parent group in [`Svg::groups`], `None` if the parent is the root.
+    pub parent: Option<usize>,
+    /// The transform from the local coordinate system of the group to the SVG user space.
+    pub abs_transform: Transform,
+}
+
+/// Where a layer of an [`Svg`] is placed when it is shown with
+/// [`SvgLayers`](crate::layers::SvgLayers).
+///
+/// Read from the `data-depth` and `data-parallax` attributes of the layer group and
+/// overridden by [`SvgLoaderSettings::layer_depths`](crate::loader::SvgLoaderSettings::layer_depths).
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Reflect, Serialize)]
+#[reflect(Default, Debug)]
+pub struct LayerDepth {
+    /// Translation of the layer along the z-axis. If it is not set, the layers are stacked
+    /// in document order with a small distance between them.
+    pub z: Option<f32>,
+    /// How much the layer moves with the world relative to the camera. `1.0` moves with
+    /// the world, `0.5` moves at half the speed like a distant background and `0.0` stays
+    /// fixed to the camera. If it is not set, the layer moves with the world.
+    pub parallax: Option<f32>,
+}
+
+impl LayerDepth {
+    /// Reads the `data-depth` and `data-parallax` attributes.
+    fn from_data(data: &HashMap<String, String>) -> Self {
+        let parse = |key: &str| data.get(key).and_then(|value| value.trim().parse().ok());
+        Self {
+            z: parse("depth"),
+            parallax: parse("parallax"),
+        }
+    }
+}
+
+/// A single path of an [`Svg`], with everything needed to tessellate it.
+#[derive(Debug, Clone, Reflect)]
 pub struct PathDescriptor {
-    pub segments: Vec<PathEvent>,
+    /// The `id` attribute of the element the path was created from, can be empty.
+    pub id: String,
+    /// The entries of the `class` attribute of the element the path was created from.
+    pub class: Vec<String>,
+    /// The `data-*` attributes of the element the path was created from, without the
+    /// `data-` prefix.
+    pub data: HashMap<String, String>,
+    /// The segments of the path in the local coordinate system of the path.
+    pub segments: Vec<PathSegment>,
+    /// The transform from the local coordinate system of the path to the SVG user space.
     pub abs_transform: Transform,
+    /// The fill or stroke color of the path.
     pub color: Color,
+    /// Whether the path is filled or stroked.
     pub draw_type: DrawType,
+    /// Index of the innermost group of the path in [`Svg::groups`], `None` if the path is
+    /// a child of the root.
+    pub group: Option<usize>,
+    /// Index of the SVG element this path was created from. The fill and stroke of an
+    /// element are separate paths with the same index.
+    pub element: usize,
+    /// Whether the path is a stroke with `vector-effect="non-scaling-stroke"`, whose width is
+    /// given in pixels on screen, see [`ATTRIBUTE_STROKE_NORMAL`].
+    pub non_scaling_stroke: bool,
 }
 
-#[derive(Debug, Clone)]
+/// How a [`PathDescriptor`] is drawn.
+#[derive(Debug, Clone, Reflect)]
 pub enum DrawType {
+    /// The inside of the path is filled.
     Fill,
-    Stroke(lyon_tessellation::StrokeOptions),
+    /// The outline of the path is stroked with the given style.
+    Stroke(StrokeStyle),
+}
+
+/// A reflectable stroke style of a [`PathDescriptor`], mirroring Lyon's
+/// [`StrokeOptions`](lyon_tessellation::StrokeOptions).
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Debug)]
+pub struct StrokeStyle {
+    /// Width of the stroke in the local coordinate system of the path.
+    pub line_width: f32,
+    /// Shape at the start of open sub-paths.
+    pub start_cap: StrokeLineCap,
+    /// Shape at the end of open sub-paths.
+    pub end_cap: StrokeLineCap,
+    /// Shape where two segments meet.
+    pub line_join: StrokeLineJoin,
+    /// Limit of the ratio between the miter length and the stroke width, see
+    /// [`StrokeLineJoin::Miter`].
+    pub miter_limit: f32,
+    /// Maximum distance between the curves and their approximation by line segments.
+    pub tolerance: f32,
+}
+
+/// The shape at the ends of a stroked sub-path, mirroring Lyon's
+/// [`LineCap`](lyon_tessellation::LineCap).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Debug)]
+pub enum StrokeLineCap {
+    /// The stroke ends at the end point.
+    Butt,
+    /// The stroke is extended by a square of half its width.
+    Square,
+    /// The stroke is extended by a half circle.
+    Round,
+}
+
+/// The shape where two segments of a stroke meet, mirroring Lyon's
+/// [`LineJoin`](lyon_tessellation::LineJoin).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+#[reflect(Debug)]
+pub enum StrokeLineJoin {
+    /// The outer edges are extended until they meet, or beveled if the miter limit is
+    /// exceeded.
+    Miter,
+    /// Like [`StrokeLineJoin::Miter`], but clipped at the miter limit instead of beveled.
+    MiterClip,
+    /// The corner is rounded.
+    Round,
+    /// The corner is cut off.
+    Bevel,
+}
+
+impl Convert<StrokeStyle> for lyon_tessellation::StrokeOptions {
+    fn convert(self) -> StrokeStyle {
+        StrokeStyle {
+            line_width: self.line_width,
+            start_cap: self.start_cap.convert(),
+            end_cap: self.end_cap.convert(),
+            line_join: self.line_join.convert(),
+            miter_limit: self.miter_limit,
+            tolerance: self.tolerance,
+        }
+    }
+}
+
+impl Convert<lyon_tessellation::StrokeOptions> for StrokeStyle {
+    fn convert(self) -> lyon_tessellation::StrokeOptions {
+        lyon_tessellation::StrokeOptions::tolerance(self.tolerance)
+            .with_line_width(self.line_width)
+            .with_start_cap(self.start_cap.convert())
+            .with_end_cap(self.end_cap.convert())
+            .with_line_join(self.line_join.convert())
+            .with_miter_limit(self.miter_limit)
+    }
+}
+
+impl Convert<StrokeLineCap> for lyon_tessellation::LineCap {
+    fn convert(self) -> StrokeLineCap {
+        match self {
+            Self::Butt => StrokeLineCap::Butt,
+            Self::Square => StrokeLineCap::Square,
+            Self::Round => StrokeLineCap::Round,
+        }
+    }
+}
+
+impl Convert<lyon_tessellation::LineCap> for StrokeLineCap {
+    fn convert(self) -> lyon_tessellation::LineCap {
+        match self {
+            Self::Butt => lyon_tessellation::LineCap::Butt,
+            Self::Square => lyon_tessellation::LineCap::Square,
+            Self::Round => lyon_tessellation::LineCap::Round,
+        }
+    }
+}
+
+impl Convert<StrokeLineJoin> for lyon_tessellation::LineJoin {
+    fn convert(self) -> StrokeLineJoin {
+        match self {
+            Self::Miter => StrokeLineJoin::Miter,
+            Self::MiterClip => StrokeLineJoin::MiterClip,
+            Self::Round => StrokeLineJoin::Round,
+            Self::Bevel => StrokeLineJoin::Bevel,
+        }
+    }
+}
+
+impl Convert<lyon_tessellation::LineJoin> for StrokeLineJoin {
+    fn convert(self) -> lyon_tessellation::LineJoin {
+        match self {
+            Self::Miter => lyon_tessellation::LineJoin::Miter,
+            Self::MiterClip => lyon_tessellation::LineJoin::MiterClip,
+            Self::Round => lyon_tessellation::LineJoin::Round,
+            Self::Bevel => lyon_tessellation::LineJoin::Bevel,
+        }
+    }
+}
+
+/// A reflectable segment of a [`PathDescriptor`], mirroring Lyon's [`PathEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Debug)]
+pub enum PathSegment {
+    /// Starts a new sub-path.
+    Begin {
+        /// Start of the sub-path.
+        at: Vec2,
+    },
+    /// A straight line.
+    Line {
+        /// Start of the line.
+        from: Vec2,
+        /// End of the line.
+        to: Vec2,
+    },
+    /// A quadratic bézier curve.
+    Quadratic {
+        /// Start of the curve.
+        from: Vec2,
+        /// Control point of the curve.
+        ctrl: Vec2,
+        /// End of the curve.
+        to: Vec2,
+    },
+    /// A cubic bézier curve.
+    Cubic {
+        /// Start of the curve.
+        from: Vec2,
+        /// First control point of the curve.
+        ctrl1: Vec2,
+        /// Second control point of the curve.
+        ctrl2: Vec2,
+        /// End of the curve.
+        to: Vec2,
+    },
+    /// Ends the current sub-path.
+    End {
+        /// Last point of the sub-path.
+        last: Vec2,
+        /// First point of the sub-path.
+        first: Vec2,
+        /// Whether the sub-path is closed.
+        close: bool,
+    },
+}
+
+impl Convert<PathSegment> for PathEvent {
+    fn convert(self) -> PathSegment {
+        let v = |p: Point| Vec2::new(p.x, p.y);
+        match self {
+            Self::Begin { at } => PathSegment::Begin { at: v(at) },
+            Self::Line { from, to } => PathSegment::Line {
+                from: v(from),
+                to: v(to),
+            },
+            Self::Quadratic { from, ctrl, to } => PathSegment::Quadratic {
+                from: v(from),
+                ctrl: v(ctrl),
+                to: v(to),
+            },
+            Self::Cubic {
+                from,
+                ctrl1,
+                ctrl2,
+                to,
+            } => PathSegment::Cubic {
+                from: v(from),
+                ctrl1: v(ctrl1),
+                ctrl2: v(ctrl2),
+                to: v(to),
+            },
+            Self::End { last, first, close } => PathSegment::End {
+                last: v(last),
+                first: v(first),
+                close,
+            },
+        }
+    }
+}
+
+impl Convert<PathEvent> for PathSegment {
+    fn convert(self) -> PathEvent {
+        let p = |v: Vec2| Point::new(v.x, v.y);
+        match self {
+            Self::Begin { at } => PathEvent::Begin { at: p(at) },
+            Self::Line { from, to } => PathEvent::Line {
+                from: p(from),
+                to: p(to),
+            },
+            Self::Quadratic { from, ctrl, to } => PathEvent::Quadratic {
+                from: p(from),
+                ctrl: p(ctrl),
+                to: p(to),
+            },
+            Self::Cubic {
+                from,
+                ctrl1,
+                ctrl2,
+                to,
+            } => PathEvent::Cubic {
+                from: p(from),
+                ctrl1: p(ctrl1),
+                ctrl2: p(ctrl2),
+                to: p(to),
+            },
+            Self::End { last, first, close } => PathEvent::End {
+                last: p(last),
+                first: p(first),
+                close,
+            },
+        }
+    }
 }
 
 // Taken from https://github.com/nical/lyon/blob/74e6b137fea70d71d3b537babae22c6652f8843e/examples/wgpu_svg/src/main.rs
@@ -169,7 +1283,7 @@ pub(crate) struct PathConvIter<'iter> {
     scale: Transform2D<f32>,
 }
 
-impl<'iter> Iterator for PathConvIter<'iter> {
+impl Iterator for PathConvIter<'_> {
     type Item = PathEvent;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -247,7 +1361,7 @@ impl<'iter> Iterator for PathConvIter<'iter> {
             }
         }
 
-        return return_event.map(|event| event.transformed(&self.scale));
+        return_event.map(|event| event.transformed(&self.scale))
     }
 }
 
@@ -265,9 +1379,21 @@ impl Convert<Point> for (f64, f64) {
     }
 }
 
+impl Convert<Transform> for usvg::Transform {
+    #[inline]
+    fn convert(self) -> Transform {
+        Transform::from_matrix(Mat4::from_cols(
+            [self.a.abs() as f32, self.b as f32, 0.0, 0.0].into(),
+            [self.c as f32, self.d.abs() as f32, 0.0, 0.0].into(),
+            [0.0, 0.0, 1.0, 0.0].into(),
+            [self.e as f32, self.f as f32, 0.0, 1.0].into(),
+        ))
+    }
+}
+
 impl<'iter> Convert<PathConvIter<'iter>> for &'iter usvg::Path {
     fn convert(self) -> PathConvIter<'iter> {
-        return PathConvIter {
+        PathConvIter {
             iter: self.data.segments(),
             first: Point::new(0.0, 0.0),
             prev: Point::new(0.0, 0.0),
@@ -279,7 +1405,7 @@ impl<'iter> Convert<PathConvIter<'iter>> for &'iter usvg::Path {
                 if self.transform.a < 0.0 { -1.0 } else { 1.0 },
                 if self.transform.d < 0.0 { -1.0 } else { 1.0 },
             ),
-        };
+        }
     }
 }
 
@@ -309,6 +1435,204 @@ impl Convert<(Color, DrawType)> for &usvg::Stroke {
             .with_line_cap(linecap)
             .with_line_join(linejoin);
 
-        return (color, DrawType::Stroke(opt));
+        (color, DrawType::Stroke(opt.convert()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{reflect::GetPath, render::mesh::VertexAttributeValues};
+
+    use super::*;
+
+    #[test]
+    fn stroke_style_round_trip() {
+        let options = lyon_tessellation::StrokeOptions::tolerance(0.5)
+            .with_line_width(3.0)
+            .with_start_cap(lyon_tessellation::LineCap::Round)
+            .with_end_cap(lyon_tessellation::LineCap::Square)
+            .with_line_join(lyon_tessellation::LineJoin::MiterClip)
+            .with_miter_limit(8.0);
+        let style: StrokeStyle = options.convert();
+        let converted: lyon_tessellation::StrokeOptions = style.convert();
+        assert_eq!(format!("{converted:?}"), format!("{options:?}"));
+    }
+
+    #[test]
+    fn stroke_style_is_reflected() {
+        let options = lyon_tessellation::StrokeOptions::tolerance(0.1).with_line_width(2.0);
+        let mut draw_type = DrawType::Stroke(options.convert());
+        *draw_type.path_mut::<f32>(".0.line_width").unwrap() = 4.0;
+        assert_eq!(draw_type.path::<f32>(".0.line_width"), Ok(&4.0));
+        assert_eq!(
+            draw_type.path::<StrokeLineJoin>(".0.line_join"),
+            Ok(&StrokeLineJoin::Miter)
+        );
+    }
+
+    #[test]
+    fn with_paths_keeps_settings() {
+        let svg = Svg {
+            name: "map.svg".into(),
+            size: Vec2::new(20.0, 10.0),
+            view_box: ViewBox {
+                x: 5.0,
+                y: 0.0,
+                w: 20.0,
+                h: 10.0,
+            },
+            groups: vec![GroupDescriptor::default()],
+            markers: vec![SvgMarker::default()],
+            path_styles: HashMap::from([(0, SvgPathStyle::default())]),
+            stroke_width: 2.0,
+            mesh: Handle::weak_from_u128(1),
+            ..Default::default()
+        };
+        let derived = svg.with_paths("map.svg#part".into(), Vec::new());
+        assert_eq!(derived.name, "map.svg#part");
+        assert_eq!(derived.size, svg.size);
+        assert_eq!(derived.view_box, svg.view_box);
+        assert_eq!(derived.groups.len(), 1);
+        assert_eq!(derived.stroke_width, 2.0);
+        assert!(derived.markers.is_empty());
+        assert!(derived.path_styles.is_empty());
+        assert_eq!(derived.mesh, Handle::default());
+    }
+
+    #[test]
+    fn ancestors_end_at_invalid_groups() {
+        let group = |id: &str, parent| GroupDescriptor {
+            id: id.into(),
+            parent,
+            ..Default::default()
+        };
+        let svg = Svg {
+            groups: vec![
+                group("root", None),
+                group("inner", Some(0)),
+                group("orphan", Some(5)),
+            ],
+            ..Default::default()
+        };
+        let ids = |group| {
+            svg.ancestors(group)
+                .map(|g| g.id.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(Some(1)), ["inner", "root"]);
+        assert_eq!(ids(Some(2)), ["orphan"]);
+        assert!(ids(Some(3)).is_empty());
+        assert!(ids(None).is_empty());
+    }
+
+    #[test]
+    fn layers_without_id() {
+        let svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg"
+                xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
+                <g inkscape:groupmode="layer" inkscape:label="Background">
+                    <rect width="10" height="10"/>
+                </g>
+                <g inkscape:groupmode="layer"><circle r="2"/></g>
+            </svg>"#,
+            "layers.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        assert_eq!(svg.layer_names(), ["Background", "Layer 2"]);
+        assert_eq!(svg.paths_in_layer("Background").count(), 1);
+        assert_eq!(svg.paths_in_layer("Layer 2").count(), 1);
+        assert!(svg.groups.iter().all(|group| group.id.is_empty()));
+    }
+
+    #[test]
+    fn retain_layers_keeps_included_and_unlayered_paths() {
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg"
+                xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
+                <g inkscape:groupmode="layer" inkscape:label="Background">
+                    <rect width="10" height="10"/>
+                </g>
+                <g inkscape:groupmode="layer" inkscape:label="Level">
+                    <rect width="5" height="5"/>
+                    <g inkscape:groupmode="layer" inkscape:label="Doors"><circle r="1"/></g>
+                </g>
+                <circle r="2"/>
+            </svg>"#,
+            "layers.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        svg.retain_layers(&LayerFilter::Include(vec!["Level".into()]));
+        assert_eq!(svg.paths.len(), 3);
+        assert_eq!(svg.paths_in_layer("Background").count(), 0);
+        assert_eq!(svg.paths_in_layer("Doors").count(), 1);
+    }
+
+    #[test]
+    fn paths_are_only_offset_with_z_offset() {
+        let mut svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <rect width="10" height="10"/><rect width="5" height="5"/>
+            </svg>"#,
+            "paint_order.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let max_z = |mesh: Mesh| match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions
+                .iter()
+                .map(|position| position[2])
+                .fold(f32::MIN, f32::max),
+            _ => panic!("the mesh has no 3D positions"),
+        };
+        assert_eq!(svg.render_3d.paint_order, PaintOrder::NoDepthWrite);
+        assert_eq!(max_z(svg.tessellate()), 0.0);
+
+        svg.render_3d.paint_order = PaintOrder::ZOffset(0.5);
+        assert_eq!(max_z(svg.tessellate()), 0.5);
+    }
+
+    #[test]
+    fn non_scaling_strokes_have_a_width_on_screen() {
+        let svg = Svg::from_bytes(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <path d="M 1 2 H 9" stroke="black" stroke-width="2" fill="none"
+                    vector-effect="non-scaling-stroke"/>
+                <path d="M 1 8 H 9" stroke="black" stroke-width="2" fill="none"/>
+            </svg>"#,
+            "hairlines.svg",
+            None::<&str>,
+        )
+        .unwrap();
+        let flags: Vec<_> = svg
+            .paths
+            .iter()
+            .map(|path| path.non_scaling_stroke)
+            .collect();
+        assert_eq!(flags, [true, false]);
+
+        // The mesh gets stroke normals without `SvgTessellation::stroke_normals`
+        let mesh = svg.tessellate();
+        let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x4(strokes)),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            mesh.attribute(ATTRIBUTE_STROKE_NORMAL),
+        )
+        else {
+            panic!("the mesh has no positions or stroke normals");
+        };
+        for (position, stroke) in positions.iter().zip(strokes) {
+            if position[1] > -5.0 {
+                // The negated half width in pixels, the vertices are on the centerline
+                assert_eq!(stroke[2], -1.0);
+                assert_eq!(position[1], -2.0);
+            } else {
+                assert_eq!(stroke[2], 1.0);
+                assert!((position[1] + 8.0).abs() > 0.5);
+            }
+        }
     }
 }
@@ -0,0 +1,414 @@
+//! Access to the raw XML of an SVG, for everything that is discarded by `usvg`.
+
+use std::{collections::HashMap, io::Read};
+
+/// Namespace of the Inkscape specific attributes.
+const INKSCAPE_NS: &str = "http://www.inkscape.org/namespaces/inkscape";
+
+/// Prefix of the `id`s given to layers and shapes with a non-scaling stroke that have none,
+/// see [`name_elements`].
+const GENERATED_ID_PREFIX: &str = "__bevy_svg_";
+
+/// The elements which are turned into paths by `usvg` with their own `id`.
+const SHAPES: [&str; 7] = [
+    "path", "rect", "circle", "ellipse", "line", "polyline", "polygon",
+];
+
+/// Decodes the bytes of an SVG or gzip compressed SVGZ file into text.
+pub(crate) fn decode(bytes: &[u8]) -> Result<String, usvg::Error> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = Vec::with_capacity(bytes.len() * 2);
+        flate2::read::GzDecoder::new(bytes)
+            .read_to_end(&mut decoded)
+            .map_err(|_| usvg::Error::MalformedGZip)?;
+        String::from_utf8(decoded).map_err(|_| usvg::Error::NotAnUtf8Str)
+    } else {
+        std::str::from_utf8(bytes)
+            .map(str::to_string)
+            .map_err(|_| usvg::Error::NotAnUtf8Str)
+    }
+}
+
+/// Parses the text of an SVG into an XML document, with the same options as `usvg`.
+pub(crate) fn parse(text: &str) -> Result<roxmltree::Document<'_>, usvg::Error> {
+    let xml_opt = roxmltree::ParsingOptions { allow_dtd: true };
+
+    roxmltree::Document::parse_with_options(text, xml_opt).map_err(usvg::Error::ParsingFailed)
+}
+
+/// Gives an `id` to every layer and every shape with `vector-effect="non-scaling-stroke"`
+/// that has none. `usvg` only keeps groups with an `id` and parses the `vector-effect`, but
+/// doesn't keep it, so without an `id` their attributes couldn't be collected.
+///
+/// The generated `id`s are removed again with [`original_id`].
+pub(crate) fn name_elements(text: String) -> Result<String, usvg::Error> {
+    if !text.contains("non-scaling-stroke") && !text.contains("<g") {
+        return Ok(text);
+    }
+    let doc = parse(&text)?;
+    let has_inkscape_layers = has_inkscape_layers(&doc);
+    let unnamed = doc
+        .descendants()
+        .filter(|node| {
+            node.is_element()
+                && node.attribute("id").is_none()
+                && (is_layer(node, has_inkscape_layers)
+                    || SHAPES.contains(&node.tag_name().name()) && has_non_scaling_stroke(node))
+        })
+        .map(|node| {
+            // The `id` is inserted right after the tag name
+            let start = node.range().start + 1;
+            let tag_length = text[start..]
+                .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                .unwrap_or(0);
+            start + tag_length
+        })
+        .collect::<Vec<_>>();
+    if unnamed.is_empty() {
+        return Ok(text);
+    }
+
+    let mut named = String::with_capacity(text.len() + unnamed.len() * 24);
+    let mut copied = 0;
+    for (i, position) in unnamed.into_iter().enumerate() {
+        named.push_str(&text[copied..position]);
+        named.push_str(" id=\"");
+        named.push_str(GENERATED_ID_PREFIX);
+        named.push_str(&i.to_string());
+        named.push('"');
+        copied = position;
+    }
+    named.push_str(&text[copied..]);
+    Ok(named)
+}
+
+/// Returns `id`, or an empty string if it was generated by [`name_elements`].
+pub(crate) fn original_id(id: &str) -> &str {
+    if id.starts_with(GENERATED_ID_PREFIX) {
+        ""
+    } else {
+        id
+    }
+}
+
+/// Whether `doc` has groups marked with `inkscape:groupmode="layer"`.
+fn has_inkscape_layers(doc: &roxmltree::Document<'_>) -> bool {
+    doc.descendants().any(|node| is_inkscape_layer(&node))
+}
+
+/// Whether `node` is marked with `inkscape:groupmode="layer"`.
+fn is_inkscape_layer(node: &roxmltree::Node<'_, '_>) -> bool {
+    node.attribute((INKSCAPE_NS, "groupmode")) == Some("layer")
+}
+
+/// Whether `node` is a layer, i.e. an Inkscape layer or, if the document has none, a
+/// top-level group.
+fn is_layer(node: &roxmltree::Node<'_, '_>, has_inkscape_layers: bool) -> bool {
+    if has_inkscape_layers {
+        is_inkscape_layer(node)
+    } else {
+        node.tag_name().name() == "g"
+            && node.parent_element() == Some(node.document().root_element())
+    }
+}
+
+/// Whether the `vector-effect` attribute or style property of `node` is `non-scaling-stroke`.
+fn has_non_scaling_stroke(node: &roxmltree::Node<'_, '_>) -> bool {
+    let style = node.attribute("style").unwrap_or_default();
+    let from_style = style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .filter(|(property, _)| property.trim() == "vector-effect")
+        .map(|(_, value)| value);
+    node.attribute("vector-effect")
+        .into_iter()
+        .chain(from_style)
+        .any(|value| value.split_whitespace().any(|v| v == "non-scaling-stroke"))
+}
+
+/// Attributes of an SVG element which are discarded by `usvg`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ElementAttributes {
+    /// The tag name of the element, e.g. `circle`.
+    pub(crate) tag: String,
+    /// The entries of the `class` attribute.
+    pub(crate) class: Vec<String>,
+    /// All `data-*` attributes, without the `data-` prefix.
+    pub(crate) data: HashMap<String, String>,
+    /// The name of the layer, if the element is a layer.
+    pub(crate) layer: Option<String>,
+    /// Whether the element has `vector-effect="non-scaling-stroke"`.
+    pub(crate) non_scaling_stroke: bool,
+}
+
+impl ElementAttributes {
+    /// Collects the attributes of all elements with an `id`, by their `id`.
+    ///
+    /// Groups marked with `inkscape:groupmode="layer"` are layers named by their
+    /// `inkscape:label`. Documents without such groups, e.g. from Illustrator, use their
+    /// top-level groups as layers, named by their `data-name`. Layers without a name use
+    /// their `id` or, if they have none, their position, e.g. `"Layer 2"` for the second
+    /// layer of the document. Layers only have an `id` for sure after [`name_elements`].
+    pub(crate) fn collect(doc: &roxmltree::Document<'_>) -> HashMap<String, Self> {
+        let has_inkscape_layers = has_inkscape_layers(doc);
+        let mut layers = 0;
+
+        doc.descendants()
+            .filter(roxmltree::Node::is_element)
+            .filter_map(|node| {
+                let is_layer = is_layer(&node, has_inkscape_layers);
+                if is_layer {
+                    layers += 1;
+                }
+                let id = node.attribute("id")?;
+                let class = node
+                    .attribute("class")
+                    .map(|class| class.split_whitespace().map(str::to_string).collect())
+                    .unwrap_or_default();
+                let data: HashMap<_, _> = node
+                    .attributes()
+                    .iter()
+                    .filter(|attr| attr.namespace().is_none())
+                    .filter_map(|attr| {
+                        let name = attr.name().strip_prefix("data-")?;
+                        Some((name.to_string(), attr.value().to_string()))
+                    })
+                    .collect();
+
+                let layer = is_layer.then(|| {
+                    let label = if has_inkscape_layers {
+                        node.attribute((INKSCAPE_NS, "label"))
+                    } else {
+                        data.get("name").map(String::as_str)
+                    };
+                    match label.unwrap_or_else(|| original_id(id)) {
+                        "" => format!("Layer {layers}"),
+                        name => name.to_string(),
+                    }
+                });
+
+                Some((
+                    id.to_string(),
+                    Self {
+                        tag: node.tag_name().name().to_string(),
+                        class,
+                        layer,
+                        data,
+                        non_scaling_stroke: has_non_scaling_stroke(&node),
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// A top-level `<symbol>` of an SVG, e.g. an icon of an icon sheet.
+pub(crate) struct Symbol {
+    /// The `id` attribute of the symbol.
+    pub(crate) id: String,
+    /// A standalone SVG document which only shows the symbol, with the viewBox of the symbol.
+    pub(crate) document: String,
+}
+
+/// Collects all `<symbol>` elements with an `id` and a `viewBox` which are children of the
+/// root or of a top-level `<defs>`.
+///
+/// Each symbol is turned into its own document, which contains all top-level `<defs>`,
+/// `<style>` and `<symbol>` elements of `text` and a `<use>` of the symbol, so references to
+/// gradients, styles or other symbols still resolve.
+pub(crate) fn symbols(doc: &roxmltree::Document<'_>, text: &str) -> Vec<Symbol> {
+    let root = doc.root_element();
+    let is_element = |node: &roxmltree::Node<'_, '_>, name: &str| {
+        node.is_element() && node.tag_name().name() == name
+    };
+
+    let shared = root
+        .children()
+        .filter(|node| {
+            ["defs", "style", "symbol"]
+                .iter()
+                .any(|n| is_element(node, n))
+        })
+        .map(|node| &text[node.range()])
+        .collect::<String>();
+    let namespaces = root
+        .namespaces()
+        .iter()
+        .filter(|ns| ns.name() != Some("xml"))
+        .map(|ns| {
+            ns.name().map_or_else(
+                || format!(" xmlns=\"{}\"", escape(ns.uri())),
+                |prefix| format!(" xmlns:{prefix}=\"{}\"", escape(ns.uri())),
+            )
+        })
+        .collect::<String>();
+
+    root.children()
+        .flat_map(|node| {
+            if is_element(&node, "defs") {
+                node.children().collect()
+            } else {
+                vec![node]
+            }
+        })
+        .filter(|node| is_element(node, "symbol"))
+        .filter_map(|node| {
+            let id = node.attribute("id")?;
+            let [x, y, w, h] = parse_view_box(node.attribute("viewBox")?)?;
+            let document = format!(
+                "<svg{namespaces} width=\"{w}\" height=\"{h}\" viewBox=\"{x} {y} {w} {h}\">\
+                 {shared}<use href=\"#{id}\" x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\"/>\
+                 </svg>",
+                id = escape(id),
+            );
+            Some(Symbol {
+                id: id.to_string(),
+                document,
+            })
+        })
+        .collect()
+}
+
+/// Parses a `viewBox` attribute, `None` if it is malformed or has no area.
+fn parse_view_box(value: &str) -> Option<[f64; 4]> {
+    let mut numbers = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|n| !n.is_empty())
+        .map(str::parse::<f64>);
+    let mut next = || numbers.next()?.ok();
+    let view_box = [next()?, next()?, next()?, next()?];
+    (view_box[2] > 0.0 && view_box[3] > 0.0).then_some(view_box)
+}
+
+/// Escapes `value` for use inside a double quoted attribute.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INKSCAPE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"
+        xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" viewBox="0 0 10 10">
+        <g inkscape:groupmode="layer" inkscape:label="Background">
+            <rect width="10" height="10"/>
+        </g>
+        <g inkscape:groupmode="layer">
+            <circle r="2"/>
+        </g>
+        <g id="named" inkscape:groupmode="layer"><rect width="1" height="1"/></g>
+    </svg>"#;
+
+    #[test]
+    fn names_layers_without_id() {
+        let text = name_elements(INKSCAPE.to_string()).unwrap();
+        let doc = parse(&text).unwrap();
+        let attributes = ElementAttributes::collect(&doc);
+        let mut layers: Vec<_> = attributes
+            .iter()
+            .filter_map(|(id, attrs)| Some((original_id(id), attrs.layer.as_deref()?)))
+            .collect();
+        layers.sort();
+        assert_eq!(
+            layers,
+            [("", "Background"), ("", "Layer 2"), ("named", "named")]
+        );
+    }
+
+    #[test]
+    fn top_level_groups_are_layers_without_inkscape_layers() {
+        let text = name_elements(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <g data-name="Sky"><g><rect width="1" height="1"/></g></g>
+                <g><rect width="1" height="1"/></g>
+            </svg>"#
+                .to_string(),
+        )
+        .unwrap();
+        let doc = parse(&text).unwrap();
+        let attributes = ElementAttributes::collect(&doc);
+        let mut layers: Vec<_> = attributes
+            .values()
+            .filter_map(|attrs| attrs.layer.as_deref())
+            .collect();
+        layers.sort_unstable();
+        assert_eq!(layers, ["Layer 2", "Sky"]);
+        assert_eq!(attributes.len(), 2, "nested groups don't get an id");
+    }
+
+    #[test]
+    fn names_non_scaling_strokes() {
+        let text = name_elements(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <path d="M0 0L1 1" vector-effect="non-scaling-stroke"/>
+                <line x2="1" style="stroke:red; vector-effect: non-scaling-stroke"/>
+                <rect width="1" height="1"/>
+            </svg>"#
+                .to_string(),
+        )
+        .unwrap();
+        let doc = parse(&text).unwrap();
+        let attributes = ElementAttributes::collect(&doc);
+        assert_eq!(attributes.len(), 2);
+        assert!(attributes.values().all(|attrs| attrs.non_scaling_stroke));
+        assert!(attributes.keys().all(|id| original_id(id).is_empty()));
+    }
+
+    #[test]
+    fn keeps_text_without_unnamed_elements() {
+        let text = r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="a"/></svg>"#;
+        assert_eq!(name_elements(text.to_string()).unwrap(), text);
+    }
+
+    #[test]
+    fn collects_classes_and_data() {
+        let doc = parse(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">
+                <circle id="spawn" class="enemy  boss" data-health="10" r="1"/>
+            </svg>"#,
+        )
+        .unwrap();
+        let attributes = ElementAttributes::collect(&doc);
+        let spawn = &attributes["spawn"];
+        assert_eq!(spawn.tag, "circle");
+        assert_eq!(spawn.class, ["enemy", "boss"]);
+        assert_eq!(spawn.data["health"], "10");
+        assert_eq!(spawn.layer, None);
+    }
+
+    #[test]
+    fn symbols_become_documents() {
+        let text = r##"<svg xmlns="http://www.w3.org/2000/svg">
+            <defs>
+                <linearGradient id="fade"/>
+                <symbol id="home" viewBox="0 0 24 12"><rect width="24" height="12"/></symbol>
+            </defs>
+            <symbol id="no_view_box"><rect width="1" height="1"/></symbol>
+            <symbol viewBox="0 0 1 1"/>
+            <symbol id="a&quot;b" viewBox="1,2 3 4"/>
+        </svg>"##;
+        let doc = parse(text).unwrap();
+        let symbols = symbols(&doc, text);
+        let ids: Vec<_> = symbols.iter().map(|symbol| symbol.id.as_str()).collect();
+        assert_eq!(ids, ["home", "a\"b"]);
+
+        let home = parse(&symbols[0].document).unwrap();
+        let root = home.root_element();
+        assert_eq!(root.attribute("viewBox"), Some("0 0 24 12"));
+        assert!(home
+            .descendants()
+            .any(|node| node.attribute("id") == Some("fade")));
+        assert!(symbols[1].document.contains(r##"href="#a&quot;b""##));
+    }
+
+    #[test]
+    fn rejects_empty_view_box() {
+        assert_eq!(parse_view_box("0 0 10 5"), Some([0.0, 0.0, 10.0, 5.0]));
+        assert_eq!(parse_view_box("0 0 0 5"), None);
+        assert_eq!(parse_view_box("0 0 10"), None);
+    }
+}